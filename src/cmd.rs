@@ -1,5 +1,5 @@
 use crate::{
-    dl::{album::AlbumArgs, DlArgs},
+    dl::{album::AlbumArgs, cleanup::CleanupArgs, DlArgs},
     sync::SyncArgs,
 };
 use clap::{Parser, Subcommand};
@@ -23,7 +23,8 @@ pub struct Cmd {
 #[derive(Subcommand)]
 pub enum Action {
     InitConfig,
-    Dl(DlArgs),
+    Dl(Box<DlArgs>),
     Sync(SyncArgs),
     Album(AlbumArgs),
+    Cleanup(CleanupArgs),
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf};
 
-use crate::dl::VideoQuality;
+use crate::dl::{CodecPreferences, QualitySpec, SponsorBlockOptions, SubtitlesOptions};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
@@ -19,15 +19,53 @@ pub struct Config {
     /// Name of the file containing the cache for sync.
     pub cache_filename: String,
 
+    /// Name of the file containing the index of downloaded videos for sync.
+    #[serde(default = "default_index_filename")]
+    pub index_filename: String,
+
+    /// Name of the file containing the state of a synchronization directory.
+    #[serde(default = "default_state_filename")]
+    pub state_filename: String,
+
+    /// Name of the lock file preventing concurrent synchronizations.
+    #[serde(default = "default_lock_filename")]
+    pub lock_filename: String,
+
+    /// Name of the optional file containing the settings of a synchronization directory.
+    #[serde(default = "default_settings_filename")]
+    pub settings_filename: String,
+
+    /// Name of the M3U8 playlist generated in each synchronization directory (`null` to disable it).
+    #[serde(default = "default_m3u_playlist_filename")]
+    pub m3u_playlist_filename: Option<String>,
+
+    /// Name of the file recording the videos whose SponsorBlock segments were handled, in their output directory.
+    #[serde(default = "default_sponsorblock_record_filename")]
+    pub sponsorblock_record_filename: String,
+
     /// Name of the file containing the automatic blacklist for sync.
     pub auto_blacklist_filename: String,
 
     /// Name of the file containing the custom blacklist for sync.
     pub custom_blacklist_filename: String,
 
+    /// Number of days after which automatically blacklisted videos are checked again
+    pub auto_blacklist_expiry_days: Option<u64>,
+
     /// Default bandwidth limit if none is provided by the platform and/or command-line arguments
     pub default_bandwidth_limit: Option<String>,
 
+    /// Default number of videos to download at the same time
+    /// if none is provided by the command-line arguments
+    pub parallel_downloads: Option<usize>,
+
+    /// Only ask for confirmation before a synchronization if it has more than this number of videos to download
+    /// (without a terminal, larger synchronizations are aborted)
+    pub sync_confirm_threshold: Option<usize>,
+
+    /// Default codec and container preferences, for platforms not specifying their own
+    pub codec_preferences: Option<CodecPreferences>,
+
     /// List of all platforms to download from
     pub platforms: HashMap<String, PlatformConfig>,
 }
@@ -39,14 +77,48 @@ impl Default for Config {
             tmp_dir: std::env::temp_dir().join("ytdl"),
             url_filename: ".ytdlsync-url".to_string(),
             cache_filename: ".ytdlsync-cache".to_string(),
+            index_filename: default_index_filename(),
+            state_filename: default_state_filename(),
+            lock_filename: default_lock_filename(),
+            settings_filename: default_settings_filename(),
+            m3u_playlist_filename: default_m3u_playlist_filename(),
+            sponsorblock_record_filename: default_sponsorblock_record_filename(),
             auto_blacklist_filename: ".ytdlsync-blacklist".to_string(),
             custom_blacklist_filename: ".ytdlsync-custom-blacklist".to_string(),
+            auto_blacklist_expiry_days: None,
             default_bandwidth_limit: None,
+            parallel_downloads: None,
+            sync_confirm_threshold: None,
+            codec_preferences: None,
             platforms: HashMap::new(),
         }
     }
 }
 
+fn default_index_filename() -> String {
+    ".ytdlsync-index".to_string()
+}
+
+fn default_state_filename() -> String {
+    ".ytdlsync-state".to_string()
+}
+
+fn default_lock_filename() -> String {
+    ".ytdlsync-lock".to_string()
+}
+
+fn default_settings_filename() -> String {
+    ".ytdlsync-settings".to_string()
+}
+
+fn default_m3u_playlist_filename() -> Option<String> {
+    Some("playlist.m3u8".to_string())
+}
+
+fn default_sponsorblock_record_filename() -> String {
+    ".ytdl-sponsorblock.json".to_string()
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct PlatformConfig {
@@ -89,6 +161,10 @@ pub struct PlatformDownloadOptions {
     /// (Only used for synchronization)
     pub rate_limited: Option<bool>,
 
+    /// Maximum number of videos from this platform to download at the same time
+    /// (Rate limited platforms are always limited to one)
+    pub max_parallel_downloads: Option<usize>,
+
     /// Use cookies from the provided browser
     pub cookies: Option<UseCookiesFrom>,
 
@@ -98,8 +174,13 @@ pub struct PlatformDownloadOptions {
     /// Output format (e.g. "mkv")
     pub output_format: Option<String>,
 
-    /// Default quality (e.g. "best-1080p")
-    pub default_quality: Option<VideoQuality>,
+    /// Default quality, either a preset (e.g. "Best1080p") or a custom quality expression
+    /// (e.g. "max-height=1080,codecs=vp9>avc")
+    pub default_quality: Option<QualitySpec>,
+
+    /// Codec and container preferences for quality presets
+    /// (falls back to the global preferences for each unspecified list)
+    pub codec_preferences: Option<CodecPreferences>,
 
     /// Raw YT-DLP download format for albums (e.g. "bestaudio")
     pub raw_album_format: Option<String>,
@@ -107,6 +188,15 @@ pub struct PlatformDownloadOptions {
     /// Disable thumbnail downloading and embedding
     pub no_thumbnail: Option<bool>,
 
+    /// Write Kodi-style NFO files for media servers (next to each video, and for each synchronization directory)
+    pub write_nfo: Option<bool>,
+
+    /// Subtitles to download
+    pub subtitles: Option<SubtitlesOptions>,
+
+    /// Mark or remove SponsorBlock segments (sponsors, intros, ...)
+    pub sponsorblock: Option<SponsorBlockOptions>,
+
     /// Additional arguments to forward to YT-DLP
     pub forward_ytdlp_args: Option<Vec<String>>,
 }
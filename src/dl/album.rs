@@ -12,10 +12,12 @@ use serde::Deserialize;
 
 use crate::{
     config::{Config, UseCookiesFrom},
-    dl::{download, parse_cookies_arg, SingleDlArgs},
+    dl::{cleanup::TmpDirLock, download, parse_cookies_arg, SingleDlArgs},
     info, success,
     utils::{
         filenames::sanitize_filename,
+        files::{move_file, MEDIA_EXTENSIONS},
+        interrupt::is_interrupted,
         platforms::{build_platform_matchers, find_platform, FoundPlatform},
         ytdlp::{fetch_playlist, RawPlaylist},
     },
@@ -49,7 +51,7 @@ pub fn download_album(args: AlbumArgs, config: &Config, cwd: &Path) -> Result<()
 
     info!("|\n| Part 1/5: Fetching playlist...\n|\n");
 
-    let RawPlaylist { entries } = fetch_playlist(
+    let RawPlaylist { entries, infos: _ } = fetch_playlist(
         &config.yt_dlp_bin,
         &url,
         platform_config
@@ -76,6 +78,8 @@ pub fn download_album(args: AlbumArgs, config: &Config, cwd: &Path) -> Result<()
         )
     })?;
 
+    let tmp_dir_lock = TmpDirLock::acquire(&tmp_dir)?;
+
     let counter_len = entries.len().to_string().len();
 
     let dl_items = entries
@@ -97,8 +101,10 @@ pub fn download_album(args: AlbumArgs, config: &Config, cwd: &Path) -> Result<()
                             .to_string(),
                     ),
                     quality: None,
-                    no_thumbnail: true,
-                    skip_repair_date: true,
+                    no_thumbnail: Some(true),
+                    skip_repair_date: Some(true),
+                    write_nfo: Some(false),
+                    no_subtitles: true,
                     cookies: cookies.clone(),
                     filenaming: Some(format!("{:0counter_len$}. %(title)s.%(ext)s", i + 1)),
                     forward_ytdlp_args: vec!["--write-info-json".to_string()],
@@ -108,7 +114,19 @@ pub fn download_album(args: AlbumArgs, config: &Config, cwd: &Path) -> Result<()
         })
         .collect::<Vec<_>>();
 
-    download(&dl_items, config, &platform_matchers)?;
+    if let Err(err) = download(&dl_items, Some(1), config, &platform_matchers, None) {
+        if is_interrupted() {
+            warn!(
+                "Album download was interrupted, removing temporary directory: {}",
+                tmp_dir.to_string_lossy().bright_magenta()
+            );
+
+            fs::remove_dir_all(&tmp_dir)
+                .context("Failed to remove the temporary download directory")?;
+        }
+
+        return Err(err);
+    }
 
     info!("|\n| Part 3/5: Analyzing tracks metadata...\n|\n");
 
@@ -125,17 +143,18 @@ pub fn download_album(args: AlbumArgs, config: &Config, cwd: &Path) -> Result<()
     let mut initial_track_metadata = None;
     let mut moves = vec![];
 
+    // Only the tracks themselves are analyzed, not their info JSON files or the directory's lock file
     for (i, dl_file) in dl_files
         .iter()
-        .filter(|c| c.path().extension().unwrap() != "json")
+        .filter(|c| {
+            c.path().extension().is_some_and(|ext| {
+                MEDIA_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str())
+            })
+        })
         .enumerate()
     {
         let dl_file = dl_file.path();
 
-        if dl_file.extension().unwrap() == "json" {
-            continue;
-        }
-
         info!(
             "| Analyzing track {:>counter_len$} / {}...",
             (i + 1).to_string().bright_yellow(),
@@ -262,11 +281,11 @@ pub fn download_album(args: AlbumArgs, config: &Config, cwd: &Path) -> Result<()
     }
 
     info!("");
-    info!("|\n| Part 5/5: Copying files to destination...\n|\n");
+    info!("|\n| Part 5/5: Moving files to destination...\n|\n");
 
     for (dl_file, track_file) in moves {
         info!(
-            "| Copying to: {}",
+            "| Moving to: {}",
             track_file
                 .strip_prefix(cwd)
                 .unwrap()
@@ -274,9 +293,11 @@ pub fn download_album(args: AlbumArgs, config: &Config, cwd: &Path) -> Result<()
                 .bright_magenta()
         );
 
-        fs::copy(&dl_file, track_file).context("Failed to copy track file to destination")?;
+        move_file(&dl_file, &track_file).context("Failed to move track file to destination")?;
     }
 
+    tmp_dir_lock.release()?;
+
     fs::remove_dir_all(&tmp_dir).context("Failed to remove the temporary download directory")?;
 
     success!("Done!");
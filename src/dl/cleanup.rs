@@ -0,0 +1,570 @@
+use std::{
+    fs::{self, File, TryLockError},
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use colored::Colorize;
+use inquire::Confirm;
+use pomsky_macro::pomsky;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::{
+    config::Config,
+    info, success,
+    utils::{
+        files::{MEDIA_EXTENSIONS, move_file, partial_file_path},
+        time::{format_duration, now_timestamp, parse_duration},
+    },
+    warn,
+};
+
+use super::{
+    EXTRACT_UPLOAD_DATE_REGEX,
+    repair_date::{apply_mtime, parse_date},
+};
+
+#[derive(Args)]
+pub struct CleanupArgs {
+    #[clap(
+        long,
+        help = "Only remove directories older than the provided duration (e.g. '12h', '7d')",
+        value_parser = parse_duration
+    )]
+    pub older_than: Option<Duration>,
+
+    #[clap(long, help = "Only list the directories that would be removed")]
+    pub dry_run: bool,
+
+    #[clap(
+        long,
+        help = "Salvage complete downloads without asking for confirmation"
+    )]
+    pub yes: bool,
+}
+
+/// Informations about a download, stored in its temporary directory
+/// so it can be salvaged if the download doesn't complete
+#[derive(Serialize, Deserialize)]
+pub struct PendingDownload {
+    pub url: String,
+    pub output_dir: PathBuf,
+    pub repair_date: bool,
+}
+
+impl PendingDownload {
+    pub fn save(&self, tmp_dir: &Path) -> Result<()> {
+        fs::write(
+            tmp_dir.join(PENDING_DOWNLOAD_FILENAME),
+            serde_json::to_string_pretty(self)
+                .context("Failed to serialize pending download informations")?,
+        )
+        .context("Failed to write pending download informations")
+    }
+
+    fn load(tmp_dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(tmp_dir.join(PENDING_DOWNLOAD_FILENAME)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
+
+/// Lock held on a temporary download directory while it's being used,
+/// so it isn't removed by `ytdl cleanup` meanwhile
+pub struct TmpDirLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl TmpDirLock {
+    pub fn acquire(tmp_dir: &Path) -> Result<Self> {
+        let path = tmp_dir.join(TMP_DIR_LOCK_FILENAME);
+
+        let file = File::create(&path).with_context(|| {
+            format!(
+                "Failed to create temporary directory lock file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })?;
+
+        file.lock()
+            .context("Failed to lock the temporary directory lock file")?;
+
+        Ok(Self { path, file })
+    }
+
+    /// Remove the lock file, before removing the temporary directory
+    pub fn release(self) -> Result<()> {
+        let Self { path, file } = self;
+
+        fs::remove_file(&path).with_context(|| {
+            format!(
+                "Failed to remove temporary directory lock file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })?;
+
+        drop(file);
+
+        Ok(())
+    }
+
+    /// Check if a temporary directory is being used by a running download
+    fn is_held(tmp_dir: &Path) -> Result<bool> {
+        let Ok(file) = File::open(tmp_dir.join(TMP_DIR_LOCK_FILENAME)) else {
+            return Ok(false);
+        };
+
+        match file.try_lock() {
+            Ok(()) => Ok(false),
+            Err(TryLockError::WouldBlock) => Ok(true),
+            Err(TryLockError::Error(err)) => Err(err).with_context(|| {
+                format!(
+                    "Failed to check the lock of temporary directory: {}",
+                    tmp_dir.to_string_lossy().bright_magenta()
+                )
+            }),
+        }
+    }
+}
+
+struct OrphanedDir {
+    path: PathBuf,
+    in_use: bool,
+    is_album: bool,
+    age: Duration,
+    size: u64,
+    salvageable: Option<SalvageableFile>,
+
+    /// Hidden files left in the output directory by a crash while moving the downloaded files
+    partial_files: Vec<PathBuf>,
+}
+
+struct SalvageableFile {
+    file: PathBuf,
+    output_file: PathBuf,
+    upload_date: Option<String>,
+}
+
+pub fn cleanup(args: CleanupArgs, config: &Config) -> Result<()> {
+    let CleanupArgs {
+        older_than,
+        dry_run,
+        yes,
+    } = args;
+
+    let mut orphaned = find_orphaned_dirs(&config.tmp_dir)?;
+
+    let in_use = orphaned.len();
+    orphaned.retain(|dir| !dir.in_use);
+    let in_use = in_use - orphaned.len();
+
+    if in_use > 0 {
+        info!(
+            "Skipping {} temporary download directories used by running downloads.",
+            in_use.to_string().bright_yellow()
+        );
+    }
+
+    if let Some(older_than) = older_than {
+        orphaned.retain(|dir| dir.age > older_than);
+    }
+
+    if orphaned.is_empty() {
+        success!("No temporary download directory to clean up!");
+        return Ok(());
+    }
+
+    orphaned.sort_by_key(|dir| std::cmp::Reverse(dir.age));
+
+    info!(
+        "Found {} temporary download directories:",
+        orphaned.len().to_string().bright_yellow()
+    );
+    info!("");
+
+    for dir in &orphaned {
+        let OrphanedDir {
+            path,
+            in_use: _,
+            is_album,
+            age,
+            size,
+            salvageable,
+            partial_files,
+        } = dir;
+
+        info!(
+            "* {} ({}{} old, {})",
+            path.file_name().unwrap().to_string_lossy().bright_magenta(),
+            if *is_album { "album, " } else { "" },
+            format_duration(*age).bright_yellow(),
+            format_size(*size).bright_cyan()
+        );
+
+        if let Some(SalvageableFile {
+            file,
+            output_file,
+            upload_date: _,
+        }) = salvageable
+        {
+            info!(
+                "  | Contains a complete download: {} => {}",
+                file.file_name().unwrap().to_string_lossy().bright_magenta(),
+                output_file.to_string_lossy().bright_magenta()
+            );
+        }
+
+        for file in partial_files {
+            info!(
+                "  | Left a partially moved file: {}",
+                file.to_string_lossy().bright_magenta()
+            );
+        }
+    }
+
+    let total_size = orphaned.iter().map(|dir| dir.size).sum::<u64>();
+
+    info!("");
+    info!(
+        "Total size: {}",
+        format_size(total_size).bright_cyan().bold()
+    );
+
+    if dry_run {
+        info!("Dry run completed!");
+        return Ok(());
+    }
+
+    let interactive = io::stdin().is_terminal();
+
+    let mut removed = 0;
+    let mut freed = 0;
+
+    for dir in &orphaned {
+        for file in &dir.partial_files {
+            fs::remove_file(file).with_context(|| {
+                format!(
+                    "Failed to remove partially moved file: {}",
+                    file.to_string_lossy().bright_magenta()
+                )
+            })?;
+        }
+
+        if let Some(salvageable) = &dir.salvageable {
+            if !interactive && !yes {
+                warn!(
+                    "Keeping {} as it contains a complete download and no terminal is available to confirm salvaging it (use {} to salvage it)",
+                    dir.path.to_string_lossy().bright_magenta(),
+                    "--yes".bright_cyan()
+                );
+
+                continue;
+            }
+
+            if !salvage(salvageable, yes)? {
+                info!("Keeping {}", dir.path.to_string_lossy().bright_magenta());
+
+                continue;
+            }
+        }
+
+        fs::remove_dir_all(&dir.path).with_context(|| {
+            format!(
+                "Failed to remove temporary download directory: {}",
+                dir.path.to_string_lossy().bright_magenta()
+            )
+        })?;
+
+        removed += 1;
+        freed += dir.size;
+    }
+
+    success!(
+        "Removed {} directories, freeing {}.",
+        removed.to_string().bright_yellow(),
+        format_size(freed).bright_cyan()
+    );
+
+    Ok(())
+}
+
+fn find_orphaned_dirs(tmp_dir: &Path) -> Result<Vec<OrphanedDir>> {
+    if !tmp_dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    let now = now_timestamp();
+
+    let mut orphaned = vec![];
+
+    let entries = fs::read_dir(tmp_dir).with_context(|| {
+        format!(
+            "Failed to read temporary downloads directory: {}",
+            tmp_dir.to_string_lossy().bright_magenta()
+        )
+    })?;
+
+    for entry in entries {
+        let entry = entry.context("Failed to read entry of the temporary downloads directory")?;
+
+        let path = entry.path();
+
+        if !path.is_dir() {
+            continue;
+        }
+
+        let filename = entry.file_name();
+
+        // Ignore directories that weren't created by `dl` or `album`
+        let Some(captured) = filename
+            .to_str()
+            .and_then(|name| TMP_DIR_NAME_REGEX.captures(name))
+        else {
+            continue;
+        };
+
+        let Ok(created) = captured.name("secs").unwrap().as_str().parse::<u64>() else {
+            continue;
+        };
+
+        let is_album = captured.name("album").is_some();
+
+        orphaned.push(OrphanedDir {
+            in_use: TmpDirLock::is_held(&path)?,
+            age: Duration::from_secs(now.saturating_sub(created)),
+            size: dir_size(&path)?,
+            salvageable: if is_album {
+                None
+            } else {
+                find_salvageable_file(&path)?
+            },
+            partial_files: find_partial_files(&path)?,
+            is_album,
+            path,
+        });
+    }
+
+    Ok(orphaned)
+}
+
+/// Find a fully downloaded media file whose output directory is known
+fn find_salvageable_file(dir: &Path) -> Result<Option<SalvageableFile>> {
+    let Some(PendingDownload {
+        url: _,
+        output_dir,
+        repair_date,
+    }) = PendingDownload::load(dir)
+    else {
+        return Ok(None);
+    };
+
+    if !output_dir.is_dir() {
+        return Ok(None);
+    }
+
+    let mut candidates = vec![];
+
+    for entry in fs::read_dir(dir).context("Failed to read temporary download directory")? {
+        let path = entry
+            .context("Failed to read entry of temporary download directory")?
+            .path();
+
+        let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        if !path.is_file() || !is_complete_media_file(filename) {
+            continue;
+        }
+
+        let Some(captured) = EXTRACT_UPLOAD_DATE_REGEX.captures(filename) else {
+            continue;
+        };
+
+        candidates.push(SalvageableFile {
+            output_file: output_dir.join(captured.name("filename").unwrap().as_str()),
+            upload_date: Some(captured.name("date").unwrap().as_str().to_owned())
+                .filter(|_| repair_date),
+            file: path.clone(),
+        });
+    }
+
+    // Multiple media files means the download didn't reach the merging step
+    Ok(if candidates.len() == 1 {
+        candidates.pop()
+    } else {
+        None
+    })
+}
+
+/// Find the hidden files a crash left in the output directory while moving the downloaded files to it
+fn find_partial_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let Some(PendingDownload {
+        url: _,
+        output_dir,
+        repair_date: _,
+    }) = PendingDownload::load(dir)
+    else {
+        return Ok(vec![]);
+    };
+
+    let mut partial_files = vec![];
+
+    for entry in fs::read_dir(dir).context("Failed to read temporary download directory")? {
+        let path = entry
+            .context("Failed to read entry of temporary download directory")?
+            .path();
+
+        let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        let Some(captured) = EXTRACT_UPLOAD_DATE_REGEX.captures(filename) else {
+            continue;
+        };
+
+        let partial_file =
+            partial_file_path(&output_dir.join(captured.name("filename").unwrap().as_str()))?;
+
+        if partial_file.is_file() {
+            partial_files.push(partial_file);
+        }
+    }
+
+    Ok(partial_files)
+}
+
+/// Salvage a complete download, returning whether its temporary directory can be removed
+///
+/// The directory is kept if the file wasn't moved, unless removing it anyway was confirmed
+fn salvage(salvageable: &SalvageableFile, yes: bool) -> Result<bool> {
+    let SalvageableFile {
+        file,
+        output_file,
+        upload_date,
+    } = salvageable;
+
+    if output_file.exists() {
+        warn!(
+            "Not salvaging {} as a file already exists at its destination",
+            file.to_string_lossy().bright_magenta()
+        );
+
+        return confirm_removal(yes);
+    }
+
+    let confirmed = yes
+        || Confirm::new(&format!(
+            "Move {} to {}?",
+            file.file_name().unwrap().to_string_lossy(),
+            output_file.to_string_lossy()
+        ))
+        .with_default(true)
+        .prompt()
+        .context("Failed to setup or retrieve confirmation prompt")?;
+
+    if !confirmed {
+        return confirm_removal(false);
+    }
+
+    move_file(file, output_file).context("Failed to move salvaged file")?;
+
+    if let Some(upload_date) = upload_date {
+        match parse_date(output_file, upload_date)? {
+            Some(date) => apply_mtime(output_file, date).with_context(|| {
+                format!(
+                    "Failed to apply modification time for file '{}'",
+                    output_file.display()
+                )
+            })?,
+            None => warn!("Could not get upload date for this video"),
+        }
+    }
+
+    success!(
+        "Salvaged file to: {}",
+        output_file.to_string_lossy().bright_magenta()
+    );
+
+    Ok(true)
+}
+
+/// Ask whether a temporary directory containing a complete download should be removed anyway
+fn confirm_removal(yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+
+    Confirm::new("Remove the temporary directory anyway?")
+        .with_default(false)
+        .prompt()
+        .context("Failed to setup or retrieve confirmation prompt")
+}
+
+/// Check if a file is a complete media file, and not a partial download, an unmerged format
+/// or an intermediate file written while merging formats (e.g. `video.temp.mkv`)
+pub fn is_complete_media_file(filename: &str) -> bool {
+    let Some((stem, ext)) = filename.rsplit_once('.') else {
+        return false;
+    };
+
+    MEDIA_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+        && !UNMERGED_FORMAT_REGEX.is_match(stem)
+        && !stem.ends_with(".temp")
+}
+
+fn dir_size(dir: &Path) -> Result<u64> {
+    let mut size = 0;
+
+    for item in WalkDir::new(dir) {
+        let item = item.context("Failed to read directory entry while computing size")?;
+
+        if item.file_type().is_file() {
+            size += item
+                .metadata()
+                .context("Failed to get file's metadata while computing size")?
+                .len();
+        }
+    }
+
+    Ok(size)
+}
+
+fn format_size(bytes: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit + 1 < units.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} {}", units[0])
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+static TMP_DIR_NAME_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(pomsky!(
+        Start :secs([digit]+) "-" [digit]+ :album("-album")? End
+    ))
+    .unwrap()
+});
+
+/// Formats downloaded separately before being merged by YT-DLP (e.g. `video.f137.mp4`)
+static UNMERGED_FORMAT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(pomsky!(".f"[digit] + End)).unwrap());
+
+pub static PENDING_DOWNLOAD_FILENAME: &str = ".ytdl-pending.json";
+
+pub static TMP_DIR_LOCK_FILENAME: &str = ".ytdl-lock";
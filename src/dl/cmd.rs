@@ -4,13 +4,24 @@ use clap::Args;
 
 use crate::config::UseCookiesFrom;
 
-use super::quality::VideoQuality;
+use super::{
+    PositionInPlaylist,
+    quality::{CodecPreferences, CustomQuality, VideoQuality},
+    subtitles::{SubtitlesFormat, SubtitlesOptions},
+};
 
 #[derive(Args, Clone)]
 pub struct DlArgs {
     #[clap(help = "URL(s) of the video/playlist/channel/... to download")]
     pub urls: Vec<String>,
 
+    #[clap(
+        short = 'j',
+        long,
+        help = "Number of videos to download at the same time"
+    )]
+    pub parallel: Option<usize>,
+
     #[clap(flatten)]
     pub dl_url: SingleDlArgs,
 }
@@ -26,10 +37,19 @@ pub struct SingleDlArgs {
     #[clap(long, help = "Video quality", conflicts_with_all = &["custom_quality", "raw_format"])]
     pub quality: Option<VideoQuality>,
 
-    #[clap(long, help = "Custom video quality", conflicts_with = "quality")]
-    pub custom_quality: Option<String>,
+    #[clap(
+        long,
+        help = "Custom video quality expression",
+        long_help = "Comma-separated list of constraints, e.g. 'max-height=1080,codecs=av1>vp9>avc,max-fps=30,hdr=off,fallback=audio'\nAvailable keys: max-height, codecs, max-fps, hdr (on/off), fallback (none/audio/best)",
+        conflicts_with_all = &["quality", "raw_format"]
+    )]
+    pub custom_quality: Option<CustomQuality>,
 
-    #[clap(long, help = "Custom YT-DLP video format", conflicts_with = "quality")]
+    #[clap(
+        long,
+        help = "Custom YT-DLP video format",
+        conflicts_with_all = &["quality", "custom_quality"]
+    )]
     pub raw_format: Option<String>,
 
     #[clap(
@@ -53,9 +73,11 @@ pub struct SingleDlArgs {
 
     #[clap(
         long,
-        help = "Prefix with the video's number in playlist (e.g. '01. <rest of the filename>')"
+        help = "Prefix with the video's number in playlist (e.g. '01. <rest of the filename>')",
+        num_args = 0,
+        default_missing_value = "true"
     )]
-    pub index_prefix: bool,
+    pub index_prefix: Option<bool>,
 
     #[clap(long, help = "Limit the download bandwidth")]
     pub limit_bandwidth: Option<String>,
@@ -68,14 +90,65 @@ pub struct SingleDlArgs {
     )]
     pub cookies: Option<UseCookiesFrom>,
 
-    #[clap(long, help = "Repair every videos' date after download")]
-    pub skip_repair_date: bool,
+    #[clap(
+        long,
+        help = "Repair every videos' date after download",
+        num_args = 0,
+        default_missing_value = "true"
+    )]
+    pub skip_repair_date: Option<bool>,
+
+    #[clap(
+        long,
+        help = "Don't download any thumbnail",
+        num_args = 0,
+        default_missing_value = "true"
+    )]
+    pub no_thumbnail: Option<bool>,
+
+    #[clap(
+        long,
+        help = "Slow down requests for rate-limited platforms",
+        num_args = 0,
+        default_missing_value = "true"
+    )]
+    pub rate_limited: Option<bool>,
+
+    #[clap(
+        long,
+        help = "Write a Kodi-style NFO file next to the video, for media servers",
+        num_args = 0,
+        default_missing_value = "true"
+    )]
+    pub write_nfo: Option<bool>,
+
+    #[clap(
+        long,
+        help = "Download subtitles in the provided languages (e.g. 'en,fr' or 'all')",
+        value_delimiter = ','
+    )]
+    pub subs: Vec<String>,
 
-    #[clap(long, help = "Don't download any thumbnail")]
-    pub no_thumbnail: bool,
+    #[clap(
+        long,
+        help = "Fall back to auto-generated subtitles",
+        requires = "subs"
+    )]
+    pub auto_subs: bool,
 
-    #[clap(long, help = "Slow down requests for rate-limited platforms")]
-    pub rate_limited: bool,
+    #[clap(
+        long,
+        help = "Embed the subtitles in the video file instead of writing them next to it",
+        requires = "subs"
+    )]
+    pub embed_subs: bool,
+
+    #[clap(
+        long,
+        help = "Convert the subtitles to the provided format",
+        requires = "subs"
+    )]
+    pub subs_format: Option<SubtitlesFormat>,
 
     #[clap(
         short,
@@ -88,6 +161,43 @@ pub struct SingleDlArgs {
     #[clap(skip)]
     /// Prefetched title of the video
     pub prefetched_title: Option<String>,
+
+    #[clap(skip)]
+    /// Output format, overriding the platform's one
+    pub output_format: Option<String>,
+
+    #[clap(skip)]
+    /// Codec and container preferences, overriding the platform's ones
+    pub codec_preferences: Option<CodecPreferences>,
+
+    #[clap(skip)]
+    /// Don't download any subtitles, even if the platform enables them
+    pub no_subtitles: bool,
+
+    #[clap(skip)]
+    /// Maximum number of videos downloaded at once from the platform, overriding the platform's one
+    pub max_parallel_downloads: Option<usize>,
+
+    #[clap(skip)]
+    /// Position of the video in its source playlist, used for the index prefix
+    /// instead of its position in the list of videos to download
+    pub position_in_playlist: Option<PositionInPlaylist>,
+}
+
+impl SingleDlArgs {
+    /// Subtitles requested using the command-line arguments
+    pub fn subtitles(&self) -> Option<SubtitlesOptions> {
+        if self.subs.is_empty() {
+            return None;
+        }
+
+        Some(SubtitlesOptions {
+            languages: self.subs.clone(),
+            auto_generated: self.auto_subs,
+            embed: self.embed_subs,
+            format: self.subs_format,
+        })
+    }
 }
 
 pub fn parse_cookies_arg(arg: &str) -> Result<UseCookiesFrom, String> {
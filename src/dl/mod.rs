@@ -1,76 +1,119 @@
 pub mod album;
+pub mod cleanup;
 mod cmd;
+mod pool;
 mod quality;
 mod repair_date;
+mod sponsorblock;
+mod subtitles;
 
 use pomsky_macro::pomsky;
 use regex::Regex;
-pub use {cmd::*, quality::*};
+pub use {cmd::*, quality::*, sponsorblock::*, subtitles::*};
 
 use crate::{
     config::{Config, PlatformDownloadOptions, UseCookiesFrom},
     dl::repair_date::{apply_mtime, parse_date},
-    error, error_anyhow, info, info_inline, success,
+    error, error_anyhow, info, info_inline,
     sync::build_approximate_index,
     utils::{
+        files::move_file,
+        interrupt::{is_interrupted, sleep_unless_interrupted},
+        nfo::write_video_nfo,
         platforms::{
             FoundPlatform, ID_REGEX_MATCHING_GROUP_NAME, PlatformsMatchers,
             determine_video_id_from_platform, find_platform, try_find_platform,
         },
-        shell::run_cmd_bi_outs,
+        shell::{run_cmd_bi_outs, run_cmd_with_progress},
         ytdlp::{append_cookies_args, fetch_playlist},
     },
     warn,
 };
 use anyhow::{Context, Result, bail};
 use colored::Colorize;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
     env, fs,
-    path::Path,
-    sync::LazyLock,
-    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    path::{Path, PathBuf},
+    sync::{
+        LazyLock,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use self::{
+    cleanup::{
+        PENDING_DOWNLOAD_FILENAME, PendingDownload, TMP_DIR_LOCK_FILENAME, TmpDirLock,
+        is_complete_media_file,
+    },
+    pool::{DownloadPool, QueuedVideo},
 };
 
-use self::quality::DEFAULT_GOOD_VIDEO_QUALITY;
+/// Log a message, or display it on the worker's progress line when downloading in parallel
+macro_rules! report {
+    ($progress: expr, $log: ident => $message: tt, $($params: tt)*) => {{
+        match $progress {
+            Some(progress) => progress.set_message(format!($message, $($params)*).trim().to_owned()),
+            None => $crate::$log!($message, $($params)*),
+        }
+    }};
+
+    ($progress: expr, $log: ident => $message: tt) => {{
+        report!($progress, $log => $message,)
+    }};
+}
 
 pub fn download_from_args(
     args: DlArgs,
     config: &Config,
     platform_matchers: &PlatformsMatchers,
 ) -> Result<()> {
-    let DlArgs { urls, dl_url } = args;
+    let DlArgs {
+        urls,
+        parallel,
+        dl_url,
+    } = args;
 
     let items = urls
         .into_iter()
         .map(|url| (url, dl_url.clone()))
         .collect::<Vec<_>>();
 
-    download(&items, config, platform_matchers)
+    download(&items, parallel, config, platform_matchers, None)
 }
 
+/// Function called with the index of each item once it's downloaded (or found to be already downloaded),
+/// along with the path of the downloaded file if it's known
+pub type OnItemCompleted<'a> = &'a (dyn Fn(usize, Option<&Path>) -> Result<()> + Sync);
+
 pub fn download(
     urls: &[(String, SingleDlArgs)],
+    parallel: Option<usize>,
     config: &Config,
     platform_matchers: &PlatformsMatchers,
+    on_completed: Option<OnItemCompleted>,
 ) -> Result<()> {
-    download_inner(urls, config, platform_matchers)
+    download_inner(urls, parallel, config, platform_matchers, on_completed)
 }
 
 fn download_inner(
     urls: &[(String, SingleDlArgs)],
+    parallel: Option<usize>,
     config: &Config,
     platform_matchers: &PlatformsMatchers,
+    on_completed: Option<OnItemCompleted>,
 ) -> Result<()> {
     for (_, args) in urls {
-        if args.no_platform && !args.skip_repair_date {
+        if args.no_platform && args.skip_repair_date != Some(true) {
             bail!("Cannot repair date without a platform\n\n{REPAIR_DATE_EXPLANATION}");
         }
     }
 
     let mut videos = Vec::with_capacity(urls.len());
 
-    for (url, args) in urls {
+    for (i, (url, args)) in urls.iter().enumerate() {
         let platform = try_find_platform(url, config, platform_matchers)?;
 
         if let Some(platform) = &platform
@@ -80,139 +123,287 @@ fn download_inner(
                 bail!("Cannot mix playlist and non-playlist downloads");
             }
 
-            return download_playlist_inner(url, args, config, platform, platform_matchers);
+            return download_playlist_inner(
+                url,
+                args,
+                parallel,
+                config,
+                platform,
+                platform_matchers,
+            );
         }
 
-        videos.push((url, args, platform));
+        videos.push(QueuedVideo {
+            index: i,
+            url,
+            args,
+            platform,
+        });
     }
 
-    let colored_total = videos.len().to_string().bright_yellow();
+    let total = videos.len();
 
-    let mut failed = 0;
-
-    let mut last_dl_from_platforms = HashMap::<&str, Instant>::new();
+    let workers = parallel
+        .or(config.parallel_downloads)
+        .unwrap_or(1)
+        .clamp(1, total.max(1));
 
     let current_dir = env::current_dir().context("Failed to get current directory")?;
 
-    for (i, (url, args, platform)) in videos.iter().enumerate() {
-        let in_playlist = if videos.len() > 1 {
-            if i > 0 {
-                info!("");
-            }
-
-            info!(
-                "> Downloading video {} / {colored_total}{}...",
-                (i + 1).to_string().bright_yellow(),
-                match &args.prefetched_title {
-                    Some(title) => format!(": {}", title.bright_magenta()),
-                    None => String::new(),
-                }
-            );
+    let pool = DownloadPool::new(videos);
+    let failed = AtomicUsize::new(0);
 
-            Some(PositionInPlaylist {
-                index: i,
-                total: videos.len(),
-            })
-        } else {
-            None
-        };
+    let worker = |progress: Option<&ProgressBar>| -> Result<()> {
+        while let Some(video) = pool.next() {
+            let result =
+                download_queued_video(&video, total, &pool, config, &current_dir, progress);
 
-        let output_dir = args.output_dir.as_deref().unwrap_or(current_dir.as_path());
-        let index = build_approximate_index(output_dir)?;
+            pool.release(&video);
 
-        if let Some(platform) = platform {
-            if let Some(video_id) =
-                determine_video_id_from_platform(url, platform.platform_matchers)
-            {
-                if index.contains(&video_id) {
-                    warn!("> Video seems to be already downloaded, skipping it.");
-                    continue;
+            match result {
+                Ok(QueuedVideoOutcome::Completed(output_file)) => {
+                    if let Some(on_completed) = on_completed
+                        && let Err(err) = on_completed(video.index, output_file.as_deref())
+                    {
+                        report!(progress, warn => "| {err:#}");
+                    }
+                }
+                Ok(QueuedVideoOutcome::Failed) => {
+                    failed.fetch_add(1, Ordering::SeqCst);
+                }
+                Err(err) => {
+                    pool.abort();
+                    return Err(err);
                 }
-            } else {
-                warn!(
-                    "Failed to determine video ID from URL using platform's matchers, can't check if video is already downloaded."
-                );
             }
         }
 
-        let rate_limited_platform_name = platform
-            .filter(|p| p.platform_config.dl_options.rate_limited == Some(true))
-            .map(|p| p.platform_name);
+        Ok(())
+    };
 
-        if args.rate_limited {
-            warn!(
-                "| Rate limited download requested, waiting {} seconds before downloading...",
-                RATE_LIMITED_WAIT_DURATION_SECS
-            );
-            std::thread::sleep(Duration::from_secs(RATE_LIMITED_WAIT_DURATION_SECS));
-        } else if let Some(last_dl) =
-            rate_limited_platform_name.and_then(|name| last_dl_from_platforms.get(name))
-        {
-            let mut remaining_wait = Duration::from_secs(RATE_LIMITED_WAIT_DURATION_SECS)
-                .saturating_sub(last_dl.elapsed());
+    if workers == 1 {
+        worker(None)?;
+    } else {
+        info!(
+            "> Downloading {} videos using {} parallel workers...",
+            total.to_string().bright_yellow(),
+            workers.to_string().bright_yellow()
+        );
+
+        let multi = MultiProgress::new();
+
+        let style = ProgressStyle::default_spinner()
+            .template("{spinner} {prefix:.bright_yellow} {wide_msg:.bright_blue}")
+            .expect("Invalid template provided for ProgressBar");
+
+        std::thread::scope(|s| {
+            let handles = (0..workers)
+                .map(|_| {
+                    let progress = multi.add(ProgressBar::new_spinner().with_style(style.clone()));
+                    progress.enable_steady_tick(Duration::from_millis(100));
+
+                    let worker = &worker;
 
-            if !remaining_wait.is_zero() {
-                // Round up
-                if remaining_wait.subsec_millis() > 0 {
-                    remaining_wait += Duration::from_secs(1);
+                    s.spawn(move || {
+                        let result = worker(Some(&progress));
+                        progress.finish_and_clear();
+                        result
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Result<Vec<_>>>()
+        })?;
+    }
+
+    if is_interrupted() {
+        bail!("Download was interrupted");
+    }
+
+    let failed = failed.into_inner();
+
+    if failed > 0 {
+        bail!(
+            "Failed with {} error(s)",
+            failed.to_string().bright_yellow()
+        );
+    }
+
+    Ok(())
+}
+
+/// Outcome of the download of a video taken from the pool
+enum QueuedVideoOutcome {
+    /// Video was downloaded or found to be already downloaded, along with the downloaded file's path if it's known
+    Completed(Option<PathBuf>),
+
+    /// Video failed to download twice, or its download was interrupted
+    Failed,
+}
+
+/// Download a video taken from the pool, retrying once in case of failure
+fn download_queued_video(
+    video: &QueuedVideo,
+    total: usize,
+    pool: &DownloadPool,
+    config: &Config,
+    current_dir: &Path,
+    progress: Option<&ProgressBar>,
+) -> Result<QueuedVideoOutcome> {
+    let QueuedVideo {
+        index: i,
+        url,
+        args,
+        platform,
+    } = *video;
+
+    let in_playlist = if total > 1 {
+        let title = match &args.prefetched_title {
+            Some(title) => format!(": {}", title.bright_magenta()),
+            None => String::new(),
+        };
+
+        match progress {
+            Some(progress) => progress.set_prefix(format!("{} / {total}{title}", i + 1)),
+            None => {
+                if i > 0 {
+                    info!("");
                 }
 
-                warn!("| Platform is rate limited!");
-                warn!(
-                    "| Waiting {} seconds before downloading from the same platform again...",
-                    remaining_wait.as_secs()
+                info!(
+                    "> Downloading video {} / {}{title}...",
+                    (i + 1).to_string().bright_yellow(),
+                    total.to_string().bright_yellow()
                 );
-
-                std::thread::sleep(Duration::from_secs(remaining_wait.as_secs()));
             }
         }
 
-        let one_try = || {
-            download_single_inner(url, *platform, args, config, in_playlist)
-                .inspect_err(|err| error_anyhow!(err))
-        };
+        Some(PositionInPlaylist { index: i, total })
+    } else {
+        None
+    };
 
-        if one_try().is_err() {
-            let wait_duration = if rate_limited_platform_name.is_some() {
-                RATE_LIMITED_WAIT_DURATION_SECS
-            } else {
-                AFTER_FAILURE_WAIT_DURATION_SECS
-            };
+    let output_dir = args.output_dir.as_deref().unwrap_or(current_dir);
+    let index = build_approximate_index(output_dir, config)?;
 
-            warn!("\nFailed on this video, waiting {wait_duration} seconds before retrying...");
+    if let Some(platform) = platform {
+        if let Some(video_id) = determine_video_id_from_platform(url, platform.platform_matchers) {
+            if index.contains(&video_id) {
+                report!(
+                    progress,
+                    warn => "> Video {} seems to be already downloaded, skipping it.",
+                    video_id.bright_magenta()
+                );
 
-            std::thread::sleep(Duration::from_secs(wait_duration));
+                return Ok(QueuedVideoOutcome::Completed(None));
+            }
+        } else {
+            report!(
+                progress,
+                warn => "Failed to determine video ID from URL using platform's matchers, can't check if video is already downloaded."
+            );
+        }
+    }
 
-            warn!("\n> Retrying...\n");
+    let rate_limited_platform_name = video.rate_limited_platform_name();
 
-            if one_try().is_err() {
-                error!("\\!/ Failed twice on this item, skipping it. \\!/\n");
-                failed += 1;
-            }
+    if args.rate_limited == Some(true) {
+        report!(
+            progress,
+            warn => "| Rate limited download requested, waiting {} seconds before downloading...",
+            RATE_LIMITED_WAIT_DURATION_SECS
+        );
+
+        if !sleep_unless_interrupted(Duration::from_secs(RATE_LIMITED_WAIT_DURATION_SECS)) {
+            return Ok(QueuedVideoOutcome::Failed);
         }
+    } else if let Some(mut remaining_wait) =
+        rate_limited_platform_name.and_then(|name| pool.remaining_wait(name))
+    {
+        // Round up
+        if remaining_wait.subsec_millis() > 0 {
+            remaining_wait += Duration::from_secs(1);
+        }
+
+        report!(progress, warn => "| Platform is rate limited!");
+        report!(
+            progress,
+            warn => "| Waiting {} seconds before downloading from the same platform again...",
+            remaining_wait.as_secs()
+        );
 
-        if let Some(platform_name) = rate_limited_platform_name {
-            last_dl_from_platforms.insert(platform_name, Instant::now());
+        if !sleep_unless_interrupted(Duration::from_secs(remaining_wait.as_secs())) {
+            return Ok(QueuedVideoOutcome::Failed);
         }
     }
 
-    if failed > 0 {
-        bail!(
-            "Failed with {} error(s)",
-            failed.to_string().bright_yellow()
-        );
+    let one_try = || {
+        download_single_inner(url, platform, args, config, in_playlist, progress).inspect_err(
+            |err| match progress {
+                Some(progress) => progress.suspend(|| error_anyhow!(err)),
+                None => error_anyhow!(err),
+            },
+        )
+    };
+
+    if let Ok(output_file) = one_try() {
+        return Ok(QueuedVideoOutcome::Completed(output_file));
     }
 
-    Ok(())
+    if is_interrupted() {
+        return Ok(QueuedVideoOutcome::Failed);
+    }
+
+    let wait_duration = if rate_limited_platform_name.is_some() {
+        RATE_LIMITED_WAIT_DURATION_SECS
+    } else {
+        AFTER_FAILURE_WAIT_DURATION_SECS
+    };
+
+    report!(
+        progress,
+        warn => "\nFailed on this video, waiting {wait_duration} seconds before retrying..."
+    );
+
+    if !sleep_unless_interrupted(Duration::from_secs(wait_duration)) {
+        return Ok(QueuedVideoOutcome::Failed);
+    }
+
+    report!(progress, warn => "\n> Retrying...\n");
+
+    let Ok(output_file) = one_try() else {
+        if is_interrupted() {
+            return Ok(QueuedVideoOutcome::Failed);
+        }
+
+        match progress {
+            Some(progress) => progress.suspend(|| {
+                error!(
+                    "\\!/ Failed twice on video {} / {total}, skipping it. \\!/\n",
+                    i + 1
+                )
+            }),
+            None => error!("\\!/ Failed twice on this item, skipping it. \\!/\n"),
+        }
+
+        return Ok(QueuedVideoOutcome::Failed);
+    };
+
+    Ok(QueuedVideoOutcome::Completed(output_file))
 }
 
+/// Download a single video, returning the path of the downloaded file if it's known
 fn download_single_inner(
     url: &str,
     platform: Option<FoundPlatform>,
     args: &SingleDlArgs,
     config: &Config,
     in_playlist: Option<PositionInPlaylist>,
-) -> Result<()> {
+    progress: Option<&ProgressBar>,
+) -> Result<Option<PathBuf>> {
     let platform_dl_options =
         platform
             .map(|p| &p.platform_config.dl_options)
@@ -220,23 +411,67 @@ fn download_single_inner(
                 bandwidth_limit: None,
                 needs_checking: None,
                 rate_limited: None,
+                max_parallel_downloads: None,
                 cookies: None,
                 skip_repair_date: None,
                 output_format: None,
                 default_quality: None,
+                codec_preferences: None,
                 raw_album_format: None,
                 no_thumbnail: None,
+                write_nfo: None,
+                subtitles: None,
+                sponsorblock: None,
                 forward_ytdlp_args: None,
             });
 
+    // Options provided in the arguments (e.g. by a synchronization directory's settings) take precedence
+    // over the platform's ones, including to disable them
+    let skip_repair_date = args
+        .skip_repair_date
+        .or(platform_dl_options.skip_repair_date)
+        .unwrap_or(false);
+
+    let no_thumbnail = args
+        .no_thumbnail
+        .or(platform_dl_options.no_thumbnail)
+        .unwrap_or(false);
+
+    let rate_limited = args
+        .rate_limited
+        .or(platform_dl_options.rate_limited)
+        .unwrap_or(false);
+
+    let write_nfo = args
+        .write_nfo
+        .or(platform_dl_options.write_nfo)
+        .unwrap_or(false);
+
+    let codec_preferences = CodecPreferences::merge(
+        args.codec_preferences
+            .as_ref()
+            .or(platform_dl_options.codec_preferences.as_ref()),
+        config.codec_preferences.as_ref(),
+    );
+
+    let format = match (&args.raw_format, &args.custom_quality, args.quality) {
+        (Some(raw_format), _, _) => raw_format.clone(),
+        (None, Some(custom_quality), _) => custom_quality.to_yt_dlp_format(),
+        (None, None, Some(quality)) => {
+            codec_preferences.apply_to_preset_format(quality.to_yt_dlp_format())
+        }
+        (None, None, None) => match &platform_dl_options.default_quality {
+            Some(quality) => quality.to_yt_dlp_format(&codec_preferences),
+            None => codec_preferences
+                .apply_to_preset_format(DEFAULT_GOOD_VIDEO_QUALITY.to_yt_dlp_format()),
+        },
+    };
+
+    let merge_output_format = codec_preferences.merge_output_format();
+
     let mut ytdl_args = vec![
         "--format",
-        args.raw_format.as_deref().unwrap_or_else(|| {
-            args.quality
-                .or(platform_dl_options.default_quality)
-                .unwrap_or(DEFAULT_GOOD_VIDEO_QUALITY)
-                .to_yt_dlp_format()
-        }),
+        &format,
         "--add-metadata",
         "--abort-on-unavailable-fragment",
         "--compat-options",
@@ -297,22 +532,94 @@ fn download_single_inner(
         Some(tmp_dir.join(format!("{}-{}", now.as_secs(), now.subsec_micros())))
     };
 
-    if tmp_dir.is_none()
-        && !platform_dl_options.skip_repair_date.unwrap_or(false)
-        && !args.skip_repair_date
-    {
+    if tmp_dir.is_none() && !skip_repair_date {
         bail!("Cannot repair date in a non-temporary directory.\n\n{REPAIR_DATE_EXPLANATION}");
     }
 
-    if !args.no_thumbnail && platform_dl_options.no_thumbnail != Some(true) {
+    if write_nfo {
+        if tmp_dir.is_none() {
+            bail!("Cannot write NFO files in a non-temporary directory");
+        }
+
+        ytdl_args.push("--write-info-json");
+    }
+
+    let output_format = args
+        .output_format
+        .as_ref()
+        .or(platform_dl_options.output_format.as_ref());
+
+    if !no_thumbnail {
         ytdl_args.push("--embed-thumbnail");
 
-        if let Some(format) = &platform_dl_options.output_format {
+        if let Some(format) = output_format {
             ytdl_args.push("--merge-output-format");
             ytdl_args.push(format);
         }
     }
 
+    // An explicit output format takes precedence over the preferred containers
+    if output_format.is_none()
+        && let Some(containers) = &merge_output_format
+    {
+        ytdl_args.push("--merge-output-format");
+        ytdl_args.push(containers);
+    }
+
+    let subtitles = if args.no_subtitles {
+        None
+    } else {
+        args.subtitles()
+            .or_else(|| platform_dl_options.subtitles.clone())
+    };
+
+    let subtitles_args = subtitles
+        .as_ref()
+        .map(SubtitlesOptions::to_yt_dlp_args)
+        .unwrap_or_default();
+
+    ytdl_args.extend(subtitles_args.iter().map(String::as_str));
+
+    let video_id = platform
+        .and_then(|platform| determine_video_id_from_platform(url, platform.platform_matchers));
+
+    let sponsorblock = match (&platform_dl_options.sponsorblock, &video_id) {
+        // Without a temporary directory, YT-DLP runs its post-processors again on already downloaded files
+        (Some(options), Some(video_id)) if tmp_dir.is_none() => {
+            match SponsorBlockRecord::load(&output_dir, config)?.status(video_id, options) {
+                SponsorBlockStatus::Unprocessed => Some(options),
+
+                SponsorBlockStatus::Processed => {
+                    report!(
+                        progress,
+                        warn => "| SponsorBlock segments were already handled for this video, not handling them again"
+                    );
+
+                    None
+                }
+
+                SponsorBlockStatus::RemovedDifferently => {
+                    report!(
+                        progress,
+                        warn => "| SponsorBlock segments were already removed from this video using other categories, not removing them again as it would cut the video further"
+                    );
+
+                    None
+                }
+            }
+        }
+
+        (Some(options), _) => Some(options),
+        (None, _) => None,
+    };
+
+    let sponsorblock_args = sponsorblock
+        .map(SponsorBlockOptions::to_yt_dlp_args)
+        .transpose()?
+        .unwrap_or_default();
+
+    ytdl_args.extend(sponsorblock_args.iter().map(String::as_str));
+
     let cookies = args
         .cookies
         .as_ref()
@@ -322,7 +629,7 @@ fn download_single_inner(
         append_cookies_args(&mut ytdl_args, cookies)?;
     }
 
-    if platform_dl_options.rate_limited == Some(true) || args.rate_limited {
+    if rate_limited {
         ytdl_args.push("--sleep-requests=3");
     }
 
@@ -332,16 +639,12 @@ fn download_single_inner(
         .unwrap_or(DEFAULT_FILENAMING)
         .to_owned();
 
-    if args.index_prefix {
-        let in_playlist = in_playlist.context(
+    if args.index_prefix == Some(true) {
+        let in_playlist = args.position_in_playlist.or(in_playlist).context(
             "Cannot add an index prefix as this video isn't part of a playlist download",
         )?;
 
-        filenaming = format!(
-            "{:0total_len$}. {filenaming}",
-            in_playlist.index + 1,
-            total_len = in_playlist.total.to_string().len()
-        )
+        filenaming = format!("{}{filenaming}", in_playlist.filename_prefix())
     };
 
     let dl_dir = match &tmp_dir {
@@ -360,8 +663,9 @@ fn download_single_inner(
 
     ytdl_args.push(url);
 
-    info!(
-        "> Downloading video {}",
+    report!(
+        progress,
+        info => "> Downloading video {}",
         match &platform {
             Some(platform) => format!("from platform {}", platform.platform_name.bright_cyan()),
             None => "without a platform".bright_yellow().to_string(),
@@ -371,17 +675,34 @@ fn download_single_inner(
     if let Some(cookies) = cookies {
         match cookies {
             UseCookiesFrom::Browser(name) => {
-                info!("| Using cookies from browser {}", name.bright_yellow())
+                report!(progress, info => "| Using cookies from browser {}", name.bright_yellow())
             }
             UseCookiesFrom::File(path) => {
-                info!("| Using cookies from file {}", path.bright_magenta())
+                report!(progress, info => "| Using cookies from file {}", path.bright_magenta())
             }
         }
     }
 
+    if let Some(subtitles) = &subtitles {
+        report!(
+            progress,
+            info => "| Downloading subtitles: {}",
+            subtitles.to_string().bright_yellow()
+        );
+    }
+
+    if let Some(sponsorblock) = sponsorblock {
+        report!(
+            progress,
+            info => "| SponsorBlock segments: {}",
+            sponsorblock.to_string().bright_yellow()
+        );
+    }
+
     if let Some(args) = &platform_dl_options.forward_ytdlp_args {
-        info!(
-            "| Forwarding additional YT-DLP arguments from platform configuration: {}",
+        report!(
+            progress,
+            info => "| Forwarding additional YT-DLP arguments from platform configuration: {}",
             args.join(" ").bright_yellow()
         );
 
@@ -389,50 +710,138 @@ fn download_single_inner(
     }
 
     if !args.forward_ytdlp_args.is_empty() {
-        info!(
-            "| Forwarding additional YT-DLP arguments from command line: {}",
+        report!(
+            progress,
+            info => "| Forwarding additional YT-DLP arguments from command line: {}",
             args.forward_ytdlp_args.join(" ").bright_yellow()
         );
         ytdl_args.extend(args.forward_ytdlp_args.iter().map(String::as_str));
     }
 
     if let Some(tmp_dir) = &tmp_dir {
-        info!(
-            "> Downloading first to temporary directory: {}",
+        report!(
+            progress,
+            info => "> Downloading first to temporary directory: {}",
             tmp_dir.to_string_lossy().bright_magenta()
         );
-        info!(
-            "> Then moving to provided final directory: {}",
+        report!(
+            progress,
+            info => "> Then moving to provided final directory: {}",
             output_dir_display.bright_magenta()
         );
     }
 
+    let tmp_dir_lock = match &tmp_dir {
+        Some(tmp_dir) => {
+            fs::create_dir(tmp_dir).with_context(|| {
+                format!(
+                    "Failed to create temporary download directory at path: {}",
+                    tmp_dir.to_string_lossy().bright_magenta()
+                )
+            })?;
+
+            let tmp_dir_lock = TmpDirLock::acquire(tmp_dir)?;
+
+            // Allows salvaging the download with `ytdl cleanup` if it isn't moved to the output directory
+            PendingDownload {
+                url: url.to_owned(),
+                output_dir: fs::canonicalize(&output_dir)
+                    .context("Failed to canonicalize output directory")?,
+                repair_date: !skip_repair_date,
+            }
+            .save(tmp_dir)?;
+
+            Some(tmp_dir_lock)
+        }
+
+        None => None,
+    };
+
+    let inspect_err = |err: &str| inspect_err(err, progress);
+
     // Actually calling YT-DLP here
-    run_cmd_bi_outs(&config.yt_dlp_bin, &ytdl_args, Some(&inspect_err))
-        .context("Failed to run YT-DLP")?;
+    let result = match progress {
+        Some(progress) => {
+            ytdl_args.push("--newline");
+            run_cmd_with_progress(&config.yt_dlp_bin, &ytdl_args, progress, Some(&inspect_err))
+        }
+        None => run_cmd_bi_outs(&config.yt_dlp_bin, &ytdl_args, Some(&inspect_err)),
+    }
+    .context("Failed to run YT-DLP");
+
+    // Partially downloaded files can't be resumed from a temporary directory with another name
+    if result.is_err()
+        && is_interrupted()
+        && let Some(tmp_dir) = &tmp_dir
+        && tmp_dir.exists()
+    {
+        report!(
+            progress,
+            warn => "> Download was interrupted, removing temporary directory: {}",
+            tmp_dir.to_string_lossy().bright_magenta()
+        );
 
+        fs::remove_dir_all(tmp_dir).with_context(|| {
+            format!(
+                "Failed to remove temporary directory at path: {}",
+                tmp_dir.to_string_lossy().bright_magenta()
+            )
+        })?;
+    }
+
+    result?;
+
+    // The record is only consulted without a temporary directory, but is also written when using one
+    // so videos downloaded before it gets disabled aren't handled again
+    let sponsorblock_record = sponsorblock.zip(video_id);
+
+    // The downloaded file's name is only known when downloading to a temporary directory
     if tmp_dir.is_none() {
-        return Ok(());
+        if let Some((options, video_id)) = sponsorblock_record {
+            SponsorBlockRecord::record(&output_dir, video_id, options, config)?;
+        }
+
+        return Ok(None);
     }
 
-    let mut files =
-        fs::read_dir(dl_dir.clone()).context("Failed to read the temporary download directory")?;
+    // YT-DLP may write other files alongside the video (subtitles, descriptions, ...)
+    let mut video_files = vec![];
+    let mut sidecar_files = vec![];
+
+    for entry in fs::read_dir(&dl_dir).context("Failed to read the temporary download directory")? {
+        let file = entry
+            .context("Failed to get informations on a downloaded file")?
+            .path();
+
+        assert!(
+            file.is_file(),
+            "Found non-file item in the temporary download directory: {}",
+            file.display()
+        );
+
+        let filename = file.file_name().unwrap().to_string_lossy();
+
+        if filename == PENDING_DOWNLOAD_FILENAME || filename == TMP_DIR_LOCK_FILENAME {
+            continue;
+        }
 
-    let video_file = files
-        .next()
-        .context("No file found in the temporary download directory")?
-        .context("Failed to get informations on the downloaded video file")?
-        .path();
+        if is_complete_media_file(&filename) {
+            video_files.push(file);
+        } else {
+            sidecar_files.push(file);
+        }
+    }
 
-    if files.next().is_some() {
-        bail!("Found more than one video in the temporary download directory");
+    // A single file with an unknown extension (e.g. a custom output format) is the video itself
+    if video_files.is_empty() && sidecar_files.len() == 1 {
+        video_files = std::mem::take(&mut sidecar_files);
     }
 
-    assert!(
-        video_file.is_file(),
-        "Found non-file item in the temporary download directory: {}",
-        video_file.display()
-    );
+    let video_file = match video_files.len() {
+        0 => bail!("No video file found in the temporary download directory"),
+        1 => video_files.pop().unwrap(),
+        _ => bail!("Found more than one video in the temporary download directory"),
+    };
 
     let video_filename = video_file.file_name().unwrap().to_str().with_context(|| {
         format!(
@@ -453,34 +862,32 @@ fn download_single_inner(
     let video_upload_date = captured.name("date").unwrap().as_str();
     let video_filename = captured.name("filename").unwrap().as_str();
 
-    let extracted_date =
-        if !args.skip_repair_date && platform_dl_options.skip_repair_date != Some(true) {
-            info!("| Extracting date from downloaded file");
-            parse_date(&video_file, video_upload_date)?
-        } else {
-            None
-        };
+    let extracted_date = if !skip_repair_date {
+        report!(progress, info => "| Extracting date from downloaded file");
 
-    info!(
-        "> Moving the download file to output directory: {}",
+        let date = parse_date(&video_file, video_upload_date)?;
+
+        if date.is_none() {
+            report!(progress, warn => "| Could not get upload date for this video");
+        }
+
+        date
+    } else {
+        None
+    };
+
+    report!(
+        progress,
+        info => "> Moving the download file to output directory: {}",
         output_dir.to_string_lossy().bright_magenta()
     );
 
     let output_file = output_dir.join(video_filename);
 
-    fs::copy(&video_file, &output_file).with_context(|| {
-        format!(
-            "Failed to move downloaded file: {}",
-            video_file.to_string_lossy().bright_magenta()
-        )
-    })?;
-
-    fs::remove_file(&video_file).with_context(|| format!("Failed to remove temporary download file at path: {}, directory will not be cleaned up",
-        video_file.to_string_lossy().bright_magenta()
-    ))?;
+    move_file(&video_file, &output_file).context("Failed to move downloaded file")?;
 
     if let Some(date) = extracted_date {
-        info!("> Applying repaired date...");
+        report!(progress, info => "> Applying repaired date...");
 
         apply_mtime(&output_file, date).with_context(|| {
             format!(
@@ -489,7 +896,85 @@ fn download_single_inner(
             )
         })?;
 
-        success!("> Successfully repaired dates!");
+        report!(progress, success => "> Successfully repaired dates!");
+    }
+
+    if write_nfo {
+        report!(progress, info => "> Writing NFO file...");
+
+        let info_json = video_file.with_extension("info.json");
+
+        write_video_nfo(&info_json, &output_file.with_extension("nfo"))?;
+
+        // Keep the info JSON file if it was explicitly requested
+        let info_json_requested = platform_dl_options
+            .forward_ytdlp_args
+            .iter()
+            .flatten()
+            .chain(&args.forward_ytdlp_args)
+            .any(|arg| arg == "--write-info-json");
+
+        if !info_json_requested {
+            fs::remove_file(&info_json).with_context(|| {
+                format!(
+                    "Failed to remove temporary info JSON file at path: {}",
+                    info_json.to_string_lossy().bright_magenta()
+                )
+            })?;
+
+            sidecar_files.retain(|file| *file != info_json);
+        }
+    }
+
+    if !sidecar_files.is_empty() {
+        report!(
+            progress,
+            info => "> Moving {} additional files (subtitles, descriptions, ...)",
+            sidecar_files.len()
+        );
+    }
+
+    for sidecar_file in &sidecar_files {
+        let sidecar_filename = sidecar_file.file_name().unwrap().to_string_lossy();
+
+        // Sidecar files share the video's name, including the upload date prefix
+        let sidecar_filename = match EXTRACT_UPLOAD_DATE_REGEX.captures(&sidecar_filename) {
+            Some(captured) => captured.name("filename").unwrap().as_str().to_owned(),
+            None => sidecar_filename.into_owned(),
+        };
+
+        let sidecar_output = output_dir.join(sidecar_filename);
+
+        if sidecar_output.exists() {
+            report!(
+                progress,
+                warn => "| Not overwriting existing file {}, discarding the downloaded one",
+                sidecar_output.to_string_lossy().bright_magenta()
+            );
+
+            fs::remove_file(sidecar_file).with_context(|| {
+                format!(
+                    "Failed to remove downloaded additional file at path: {}",
+                    sidecar_file.to_string_lossy().bright_magenta()
+                )
+            })?;
+
+            continue;
+        }
+
+        move_file(sidecar_file, &sidecar_output)
+            .context("Failed to move downloaded additional file")?;
+    }
+
+    if let Some((options, video_id)) = sponsorblock_record {
+        SponsorBlockRecord::record(&output_dir, video_id, options, config)?;
+    }
+
+    fs::remove_file(dl_dir.join(PENDING_DOWNLOAD_FILENAME))
+        .context("Failed to remove pending download informations")?;
+
+    if let Some(tmp_dir_lock) = tmp_dir_lock {
+        tmp_dir_lock.release()?;
     }
 
     fs::remove_dir(&dl_dir).with_context(|| {
@@ -499,9 +984,9 @@ fn download_single_inner(
         )
     })?;
 
-    success!("> Done!");
+    report!(progress, success => "> Done!");
 
-    Ok(())
+    Ok(Some(output_file))
 }
 
 static EXTRACT_UPLOAD_DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
@@ -514,6 +999,7 @@ static EXTRACT_UPLOAD_DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
 fn download_playlist_inner(
     playlist_url: &str,
     args: &SingleDlArgs,
+    parallel: Option<usize>,
     config: &Config,
     platform: &FoundPlatform,
     platform_matchers: &PlatformsMatchers,
@@ -582,19 +1068,19 @@ fn download_playlist_inner(
         ));
     }
 
-    download_inner(&urls, config, platform_matchers)
+    download_inner(&urls, parallel, config, platform_matchers, None)
 }
 
-fn inspect_err(err: &str) {
+fn inspect_err(err: &str, progress: Option<&ProgressBar>) {
     if !err.contains("HTTP Error 429: Too Many Requests.") {
         return;
     }
 
-    warn!("Failed due to too many requests being made to server.");
+    report!(progress, warn => "Failed due to too many requests being made to server.");
 
     let mut remaining = 15 * 60;
 
-    while remaining > 0 {
+    while remaining > 0 && !is_interrupted() {
         let remaining_msg = format!(
             "{}{}s",
             if remaining > 60 {
@@ -608,16 +1094,32 @@ fn inspect_err(err: &str) {
 
         let message = format!(">> Waiting before retry... {}", remaining_msg).bright_yellow();
 
-        info_inline!("\r{}", message);
+        match progress {
+            Some(progress) => progress.set_message(message.to_string()),
+            None => info_inline!("\r{}", message),
+        }
+
         std::thread::sleep(Duration::from_secs(1));
         remaining -= 1;
     }
 }
 
-#[derive(Clone, Copy)]
-struct PositionInPlaylist {
-    index: usize,
-    total: usize,
+/// Position of a video in its playlist
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct PositionInPlaylist {
+    pub index: usize,
+    pub total: usize,
+}
+
+impl PositionInPlaylist {
+    /// Prefix put before the filename of the video (e.g. '01. ')
+    pub fn filename_prefix(&self) -> String {
+        format!(
+            "{:0total_len$}. ",
+            self.index + 1,
+            total_len = self.total.to_string().len()
+        )
+    }
 }
 
 static AFTER_FAILURE_WAIT_DURATION_SECS: u64 = 5;
@@ -0,0 +1,144 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::utils::{interrupt::is_interrupted, platforms::FoundPlatform};
+
+use super::{RATE_LIMITED_WAIT_DURATION_SECS, SingleDlArgs};
+
+/// Queue of videos shared between download workers
+///
+/// Enforces the per-platform concurrency caps, and keeps track of the last download
+/// from each rate-limited platform to space them out.
+pub struct DownloadPool<'a, 'b> {
+    state: Mutex<PoolState<'a, 'b>>,
+    available: Condvar,
+}
+
+struct PoolState<'a, 'b> {
+    pending: VecDeque<QueuedVideo<'a, 'b>>,
+    running: HashMap<&'a str, usize>,
+    last_dl_from_platforms: HashMap<&'a str, Instant>,
+}
+
+#[derive(Clone, Copy)]
+pub struct QueuedVideo<'a, 'b> {
+    pub index: usize,
+    pub url: &'a str,
+    pub args: &'a SingleDlArgs,
+    pub platform: Option<FoundPlatform<'a, 'b>>,
+}
+
+impl<'a, 'b> QueuedVideo<'a, 'b> {
+    /// Name of the platform if it is rate limited (unless overridden by the video's arguments)
+    pub fn rate_limited_platform_name(&self) -> Option<&'a str> {
+        self.platform
+            .filter(|p| {
+                self.args
+                    .rate_limited
+                    .or(p.platform_config.dl_options.rate_limited)
+                    == Some(true)
+            })
+            .map(|p| p.platform_name)
+    }
+
+    /// Maximum number of videos from the same platform that can be downloaded at once
+    fn platform_cap(&self) -> usize {
+        match self.platform {
+            None => usize::MAX,
+            Some(_) if self.rate_limited_platform_name().is_some() => 1,
+            Some(platform) => self
+                .args
+                .max_parallel_downloads
+                .or(platform.platform_config.dl_options.max_parallel_downloads)
+                .unwrap_or(usize::MAX)
+                .max(1),
+        }
+    }
+}
+
+impl<'a, 'b> DownloadPool<'a, 'b> {
+    pub fn new(videos: Vec<QueuedVideo<'a, 'b>>) -> Self {
+        Self {
+            state: Mutex::new(PoolState {
+                pending: videos.into(),
+                running: HashMap::new(),
+                last_dl_from_platforms: HashMap::new(),
+            }),
+            available: Condvar::new(),
+        }
+    }
+
+    /// Take the next video that can be downloaded without exceeding its platform's cap
+    ///
+    /// Blocks until such a video is available, returns `None` once the queue is empty
+    /// or if the program was interrupted.
+    pub fn next(&self) -> Option<QueuedVideo<'a, 'b>> {
+        let mut state = self.state.lock().unwrap();
+
+        loop {
+            if state.pending.is_empty() || is_interrupted() {
+                return None;
+            }
+
+            let eligible = state.pending.iter().position(|video| match video.platform {
+                None => true,
+                Some(platform) => {
+                    state
+                        .running
+                        .get(platform.platform_name)
+                        .copied()
+                        .unwrap_or(0)
+                        < video.platform_cap()
+                }
+            });
+
+            if let Some(pos) = eligible {
+                let video = state.pending.remove(pos).unwrap();
+
+                if let Some(platform) = video.platform {
+                    *state.running.entry(platform.platform_name).or_default() += 1;
+                }
+
+                return Some(video);
+            }
+
+            state = self.available.wait(state).unwrap();
+        }
+    }
+
+    /// Compute how long to wait before downloading from a rate-limited platform again
+    pub fn remaining_wait(&self, platform_name: &str) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+
+        let last_dl = state.last_dl_from_platforms.get(platform_name)?;
+
+        Some(Duration::from_secs(RATE_LIMITED_WAIT_DURATION_SECS).saturating_sub(last_dl.elapsed()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Mark a video taken with [`DownloadPool::next`] as treated
+    pub fn release(&self, video: &QueuedVideo<'a, 'b>) {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(platform) = video.platform {
+            *state.running.get_mut(platform.platform_name).unwrap() -= 1;
+        }
+
+        if let Some(platform_name) = video.rate_limited_platform_name() {
+            state
+                .last_dl_from_platforms
+                .insert(platform_name, Instant::now());
+        }
+
+        self.available.notify_all();
+    }
+
+    /// Drop all pending videos, making workers stop after their current download
+    pub fn abort(&self) {
+        self.state.lock().unwrap().pending.clear();
+        self.available.notify_all();
+    }
+}
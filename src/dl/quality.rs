@@ -1,3 +1,5 @@
+use std::{fmt, str::FromStr};
+
 use serde::{Deserialize, Serialize};
 
 macro_rules! make_video_quality_format {
@@ -52,3 +54,405 @@ impl VideoQuality {
         }
     }
 }
+
+/// Preferred codecs and containers, each list going from most to least preferred
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CodecPreferences {
+    /// Preferred video codecs (e.g. ["vp9", "avc"])
+    pub video_codecs: Option<Vec<VideoCodec>>,
+
+    /// Preferred audio codecs (e.g. ["opus", "aac"])
+    pub audio_codecs: Option<Vec<AudioCodec>>,
+
+    /// Preferred containers (e.g. ["mp4"])
+    pub containers: Option<Vec<String>>,
+}
+
+impl CodecPreferences {
+    /// Use the platform's preferences, falling back to the global ones for each list
+    pub fn merge(platform: Option<&Self>, global: Option<&Self>) -> Self {
+        Self {
+            video_codecs: platform
+                .and_then(|p| p.video_codecs.as_ref())
+                .or(global.and_then(|g| g.video_codecs.as_ref()))
+                .cloned(),
+            audio_codecs: platform
+                .and_then(|p| p.audio_codecs.as_ref())
+                .or(global.and_then(|g| g.audio_codecs.as_ref()))
+                .cloned(),
+            containers: platform
+                .and_then(|p| p.containers.as_ref())
+                .or(global.and_then(|g| g.containers.as_ref()))
+                .cloned(),
+        }
+    }
+
+    /// Apply the preferences to a quality preset's format
+    ///
+    /// Each alternative of the format is tried with every preferred codec in order before being tried as is,
+    /// so the resolution chosen by the preset is unaffected and it's still used when nothing matches the preferences.
+    pub fn apply_to_preset_format(&self, format: &str) -> String {
+        let video_filters = self
+            .video_codecs
+            .iter()
+            .flatten()
+            .map(|codec| codec.to_yt_dlp_filter())
+            .chain([String::new()])
+            .collect::<Vec<_>>();
+
+        let audio_filters = self
+            .audio_codecs
+            .iter()
+            .flatten()
+            .map(|codec| codec.to_yt_dlp_filter())
+            .chain([String::new()])
+            .collect::<Vec<_>>();
+
+        let mut formats = vec![];
+
+        for alternative in format.split('/') {
+            for video_filter in &video_filters {
+                for audio_filter in &audio_filters {
+                    formats.push(match alternative.split_once('+') {
+                        Some((video, audio)) => {
+                            format!("{video}{video_filter}+{audio}{audio_filter}")
+                        }
+                        None => format!("{alternative}{video_filter}{audio_filter}"),
+                    });
+                }
+            }
+        }
+
+        formats.join("/")
+    }
+
+    /// Containers the video and audio streams may be merged into, in YT-DLP's `--merge-output-format` syntax
+    pub fn merge_output_format(&self) -> Option<String> {
+        self.containers
+            .as_ref()
+            .filter(|containers| !containers.is_empty())
+            .map(|containers| containers.join("/"))
+    }
+}
+
+/// Quality to download videos in, either a preset or a custom expression
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QualitySpec {
+    Preset(VideoQuality),
+    Custom(CustomQuality),
+}
+
+impl QualitySpec {
+    /// Codec preferences only apply to presets, as custom expressions already choose their codecs
+    pub fn to_yt_dlp_format(&self, codec_preferences: &CodecPreferences) -> String {
+        match self {
+            QualitySpec::Preset(quality) => {
+                codec_preferences.apply_to_preset_format(quality.to_yt_dlp_format())
+            }
+            QualitySpec::Custom(quality) => quality.to_yt_dlp_format(),
+        }
+    }
+}
+
+/// Custom quality expression
+///
+/// Made of comma-separated `key=value` clauses, e.g. `max-height=1080,codecs=av1>vp9>avc,max-fps=30,hdr=off,fallback=audio`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CustomQuality {
+    /// Maximum height of the video
+    pub max_height: Option<u32>,
+
+    /// Preferred video codecs, from most to least preferred
+    pub codecs: Vec<VideoCodec>,
+
+    /// Maximum framerate of the video
+    pub max_fps: Option<u32>,
+
+    /// Prefer (`Some(true)`) or exclude (`Some(false)`) HDR formats
+    pub hdr: Option<bool>,
+
+    /// What to download when no format matches the constraints
+    pub fallback: QualityFallback,
+}
+
+impl CustomQuality {
+    pub fn to_yt_dlp_format(&self) -> String {
+        let Self {
+            max_height,
+            codecs,
+            max_fps,
+            hdr,
+            fallback,
+        } = self;
+
+        let mut base_filters = String::new();
+
+        if let Some(max_height) = max_height {
+            base_filters.push_str(&format!("[height<=?{max_height}]"));
+        }
+
+        if let Some(max_fps) = max_fps {
+            base_filters.push_str(&format!("[fps<=?{max_fps}]"));
+        }
+
+        let hdr_filters: &[&str] = match hdr {
+            None => &[""],
+            Some(true) => &["[dynamic_range!=SDR]", ""],
+            Some(false) => &["[dynamic_range=?SDR]"],
+        };
+
+        let codec_filters = codecs
+            .iter()
+            .map(|codec| codec.to_yt_dlp_filter())
+            .chain([String::new()])
+            .collect::<Vec<_>>();
+
+        let mut formats = vec![];
+
+        for hdr_filter in hdr_filters {
+            for codec_filter in &codec_filters {
+                let filters = format!("{base_filters}{hdr_filter}{codec_filter}");
+                formats.push(format!("bestvideo*{filters}+bestaudio/best{filters}"));
+            }
+        }
+
+        match fallback {
+            QualityFallback::None => {}
+            QualityFallback::Audio => formats.push("bestaudio".to_owned()),
+            QualityFallback::Best => formats.push(make_video_quality_format!(=fallback).to_owned()),
+        }
+
+        formats.join("/")
+    }
+}
+
+impl FromStr for CustomQuality {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut quality = Self::default();
+        let mut seen_keys = vec![];
+
+        for clause in input.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            let (key, value) = clause
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| format!("Invalid clause '{clause}', expected 'key=value'"))?;
+
+            if seen_keys.contains(&key) {
+                return Err(format!("Key '{key}' is specified multiple times"));
+            }
+
+            seen_keys.push(key);
+
+            match key {
+                "max-height" => {
+                    quality.max_height = Some(
+                        value
+                            .strip_suffix('p')
+                            .unwrap_or(value)
+                            .parse()
+                            .map_err(|_| format!("Invalid maximum height: {value}"))?,
+                    )
+                }
+
+                "codecs" => {
+                    quality.codecs = value
+                        .split('>')
+                        .map(|codec| codec.trim().parse())
+                        .collect::<Result<_, _>>()?
+                }
+
+                "max-fps" => {
+                    quality.max_fps = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid maximum framerate: {value}"))?,
+                    )
+                }
+
+                "hdr" => {
+                    quality.hdr = Some(match value {
+                        "on" => true,
+                        "off" => false,
+                        _ => {
+                            return Err(format!(
+                                "Invalid HDR value '{value}', expected 'on' or 'off'"
+                            ));
+                        }
+                    })
+                }
+
+                "fallback" => {
+                    quality.fallback = match value {
+                        "none" => QualityFallback::None,
+                        "audio" => QualityFallback::Audio,
+                        "best" => QualityFallback::Best,
+                        _ => {
+                            return Err(format!(
+                                "Invalid fallback '{value}', expected 'none', 'audio' or 'best'"
+                            ));
+                        }
+                    }
+                }
+
+                _ => return Err(format!("Unknown quality key: {key}")),
+            }
+        }
+
+        Ok(quality)
+    }
+}
+
+impl fmt::Display for CustomQuality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            max_height,
+            codecs,
+            max_fps,
+            hdr,
+            fallback,
+        } = self;
+
+        let mut clauses = vec![];
+
+        if let Some(max_height) = max_height {
+            clauses.push(format!("max-height={max_height}"));
+        }
+
+        if !codecs.is_empty() {
+            clauses.push(format!(
+                "codecs={}",
+                codecs
+                    .iter()
+                    .map(VideoCodec::to_string)
+                    .collect::<Vec<_>>()
+                    .join(">")
+            ));
+        }
+
+        if let Some(max_fps) = max_fps {
+            clauses.push(format!("max-fps={max_fps}"));
+        }
+
+        if let Some(hdr) = hdr {
+            clauses.push(format!("hdr={}", if *hdr { "on" } else { "off" }));
+        }
+
+        match fallback {
+            QualityFallback::None => {}
+            QualityFallback::Audio => clauses.push("fallback=audio".to_owned()),
+            QualityFallback::Best => clauses.push("fallback=best".to_owned()),
+        }
+
+        write!(f, "{}", clauses.join(","))
+    }
+}
+
+impl TryFrom<String> for CustomQuality {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<CustomQuality> for String {
+    fn from(value: CustomQuality) -> Self {
+        value.to_string()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QualityFallback {
+    #[default]
+    None,
+    Audio,
+    Best,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VideoCodec {
+    Av1,
+    Vp9,
+    #[serde(alias = "h265")]
+    Hevc,
+    #[serde(alias = "h264")]
+    Avc,
+}
+
+impl VideoCodec {
+    /// Regex matching the codec in YT-DLP's `vcodec` field
+    fn ytdlp_regex(self) -> &'static str {
+        match self {
+            VideoCodec::Av1 => "^av0?1",
+            VideoCodec::Vp9 => "^vp0?9",
+            VideoCodec::Hevc => "^(hvc1|hev1|h265)",
+            VideoCodec::Avc => "^(avc1|h264)",
+        }
+    }
+
+    pub fn to_yt_dlp_filter(self) -> String {
+        format!("[vcodec~='{}']", self.ytdlp_regex())
+    }
+}
+
+impl FromStr for VideoCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "av1" => Ok(VideoCodec::Av1),
+            "vp9" => Ok(VideoCodec::Vp9),
+            "hevc" | "h265" => Ok(VideoCodec::Hevc),
+            "avc" | "h264" => Ok(VideoCodec::Avc),
+            _ => Err(format!(
+                "Unknown video codec '{s}', expected 'av1', 'vp9', 'hevc' or 'avc'"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for VideoCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            VideoCodec::Av1 => "av1",
+            VideoCodec::Vp9 => "vp9",
+            VideoCodec::Hevc => "hevc",
+            VideoCodec::Avc => "avc",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCodec {
+    Opus,
+    Aac,
+    Vorbis,
+    Mp3,
+    Flac,
+}
+
+impl AudioCodec {
+    /// Regex matching the codec in YT-DLP's `acodec` field
+    fn ytdlp_regex(self) -> &'static str {
+        match self {
+            AudioCodec::Opus => "^opus",
+            AudioCodec::Aac => "^(mp4a|aac)",
+            AudioCodec::Vorbis => "^vorbis",
+            AudioCodec::Mp3 => "^mp3",
+            AudioCodec::Flac => "^flac",
+        }
+    }
+
+    pub fn to_yt_dlp_filter(self) -> String {
+        format!("[acodec~='{}']", self.ytdlp_regex())
+    }
+}
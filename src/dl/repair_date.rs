@@ -1,10 +1,13 @@
-use crate::{utils::shell::run_custom_cmd, warn};
+use crate::utils::shell::run_custom_cmd;
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
 use pomsky_macro::pomsky;
 use regex::Regex;
 use std::{path::Path, process::Command, sync::LazyLock};
 
+/// Parse the upload date extracted from a downloaded file's name
+///
+/// Returns `None` if the upload date is unavailable; warning about it is left to the caller
 pub fn parse_date(file: &Path, date: &str) -> Result<Option<UploadDate>> {
     assert!(
         file.is_file(),
@@ -13,7 +16,6 @@ pub fn parse_date(file: &Path, date: &str) -> Result<Option<UploadDate>> {
     );
 
     if date == "NA" {
-        warn!("Could not get upload date for this video");
         return Ok(None);
     }
 
@@ -0,0 +1,287 @@
+use std::{collections::HashMap, fmt, fs, path::Path, sync::Mutex};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, utils::time::now_timestamp};
+
+/// Handling of SponsorBlock segments
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SponsorBlockOptions {
+    /// Categories of segments to handle
+    pub categories: Vec<SponsorBlockCategory>,
+
+    /// What to do with the segments
+    pub mode: SponsorBlockMode,
+
+    /// URL of the SponsorBlock API, to use a mirror instead of the official instance
+    pub api_url: Option<String>,
+}
+
+impl SponsorBlockOptions {
+    /// Build the YT-DLP arguments to handle the segments
+    pub fn to_yt_dlp_args(&self) -> Result<Vec<String>> {
+        let Self {
+            categories,
+            mode,
+            api_url,
+        } = self;
+
+        if categories.is_empty() {
+            bail!("No SponsorBlock category provided");
+        }
+
+        if *mode == SponsorBlockMode::Remove
+            && let Some(category) = categories.iter().find(|category| !category.is_removable())
+        {
+            bail!(
+                "SponsorBlock category {} can only be marked, not removed",
+                category.to_string().bright_yellow()
+            );
+        }
+
+        let mut args = vec![
+            match mode {
+                SponsorBlockMode::Mark => "--sponsorblock-mark",
+                SponsorBlockMode::Remove => "--sponsorblock-remove",
+            }
+            .to_owned(),
+            categories
+                .iter()
+                .map(SponsorBlockCategory::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+        ];
+
+        if let Some(api_url) = api_url {
+            args.push("--sponsorblock-api".to_owned());
+            args.push(api_url.clone());
+        }
+
+        Ok(args)
+    }
+
+    /// Check if the segments of a video were already handled the same way
+    fn matches(&self, processed: &SponsorBlockProcessed) -> bool {
+        let processed_categories = expand_categories(&processed.categories, processed.mode);
+
+        self.mode == processed.mode
+            && expand_categories(&self.categories, self.mode)
+                .iter()
+                .all(|category| processed_categories.contains(category))
+    }
+}
+
+impl fmt::Display for SponsorBlockOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            match self.mode {
+                SponsorBlockMode::Mark => "mark",
+                SponsorBlockMode::Remove => "remove",
+            },
+            self.categories
+                .iter()
+                .map(SponsorBlockCategory::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SponsorBlockMode {
+    /// Mark the segments as chapters
+    Mark,
+
+    /// Cut the segments out of the video
+    Remove,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SponsorBlockCategory {
+    Sponsor,
+    Intro,
+    Outro,
+    Selfpromo,
+    Preview,
+    Filler,
+    Interaction,
+    MusicOfftopic,
+    PoiHighlight,
+    Chapter,
+    All,
+}
+
+impl SponsorBlockCategory {
+    /// Every category, except the one standing for all of them
+    const EVERY: [Self; 10] = [
+        Self::Sponsor,
+        Self::Intro,
+        Self::Outro,
+        Self::Selfpromo,
+        Self::Preview,
+        Self::Filler,
+        Self::Interaction,
+        Self::MusicOfftopic,
+        Self::PoiHighlight,
+        Self::Chapter,
+    ];
+
+    fn is_removable(self) -> bool {
+        !matches!(self, Self::PoiHighlight | Self::Chapter)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Sponsor => "sponsor",
+            Self::Intro => "intro",
+            Self::Outro => "outro",
+            Self::Selfpromo => "selfpromo",
+            Self::Preview => "preview",
+            Self::Filler => "filler",
+            Self::Interaction => "interaction",
+            Self::MusicOfftopic => "music_offtopic",
+            Self::PoiHighlight => "poi_highlight",
+            Self::Chapter => "chapter",
+            Self::All => "all",
+        }
+    }
+}
+
+impl fmt::Display for SponsorBlockCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Replace the category standing for all of them with the categories it includes (as YT-DLP does, only the
+/// removable ones when removing segments)
+fn expand_categories(
+    categories: &[SponsorBlockCategory],
+    mode: SponsorBlockMode,
+) -> Vec<SponsorBlockCategory> {
+    if !categories.contains(&SponsorBlockCategory::All) {
+        return categories.to_vec();
+    }
+
+    SponsorBlockCategory::EVERY
+        .into_iter()
+        .filter(|category| mode == SponsorBlockMode::Mark || category.is_removable())
+        .collect()
+}
+
+/// Record of the videos whose SponsorBlock segments were handled, persisted in their output directory
+///
+/// YT-DLP runs its post-processors again on files it finds already downloaded, which would cut
+/// the segments of an already cut video a second time.
+#[derive(Serialize, Deserialize, Default)]
+pub struct SponsorBlockRecord {
+    /// Handled videos, by ID
+    videos: HashMap<String, SponsorBlockProcessed>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SponsorBlockProcessed {
+    mode: SponsorBlockMode,
+    categories: Vec<SponsorBlockCategory>,
+
+    /// UNIX timestamp (in seconds) of the processing
+    processed_at: u64,
+}
+
+impl SponsorBlockRecord {
+    /// Load the record of an output directory
+    pub fn load(output_dir: &Path, config: &Config) -> Result<Self> {
+        let path = output_dir.join(&config.sponsorblock_record_filename);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let record = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Failed to read SponsorBlock record file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })?;
+
+        serde_json::from_str(&record).with_context(|| {
+            format!(
+                "Failed to decode SponsorBlock record file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        fs::write(
+            path,
+            serde_json::to_string_pretty(self)
+                .context("Failed to serialize SponsorBlock record")?,
+        )
+        .with_context(|| {
+            format!(
+                "Failed to write SponsorBlock record file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })
+    }
+
+    /// Check if a video's segments were already handled, and how it compares to the provided options
+    pub fn status(&self, video_id: &str, options: &SponsorBlockOptions) -> SponsorBlockStatus {
+        match self.videos.get(video_id) {
+            None => SponsorBlockStatus::Unprocessed,
+            Some(processed) if options.matches(processed) => SponsorBlockStatus::Processed,
+            Some(processed) if processed.mode == SponsorBlockMode::Remove => {
+                SponsorBlockStatus::RemovedDifferently
+            }
+            // Segments which were only marked can be handled again
+            Some(_) => SponsorBlockStatus::Unprocessed,
+        }
+    }
+
+    /// Record that a video's segments were handled, in the record file of its output directory
+    pub fn record(
+        output_dir: &Path,
+        video_id: String,
+        options: &SponsorBlockOptions,
+        config: &Config,
+    ) -> Result<()> {
+        let path = output_dir.join(&config.sponsorblock_record_filename);
+
+        // Videos may be downloaded to the same directory in parallel
+        let _guard = RECORD_FILES_LOCK.lock().unwrap();
+
+        let mut record = Self::load(output_dir, config)?;
+
+        record.videos.insert(
+            video_id,
+            SponsorBlockProcessed {
+                mode: options.mode,
+                categories: options.categories.clone(),
+                processed_at: now_timestamp(),
+            },
+        );
+
+        record.save(&path)
+    }
+}
+
+/// Status of a video in the SponsorBlock record
+pub enum SponsorBlockStatus {
+    Unprocessed,
+
+    /// Segments were handled using the same options
+    Processed,
+
+    /// Segments were cut using other options (e.g. fewer categories), cutting them again would cut the video further
+    RemovedDifferently,
+}
+
+static RECORD_FILES_LOCK: Mutex<()> = Mutex::new(());
@@ -0,0 +1,117 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Subtitles to download along with the videos
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SubtitlesOptions {
+    /// Languages to download (e.g. "en" or "fr"), YT-DLP's language regexes and "all" are supported as well
+    pub languages: Vec<String>,
+
+    /// Fall back to auto-generated subtitles for languages without regular subtitles
+    #[serde(default)]
+    pub auto_generated: bool,
+
+    /// Embed the subtitles in the video file instead of writing them next to it
+    #[serde(default)]
+    pub embed: bool,
+
+    /// Convert the subtitles to this format
+    pub format: Option<SubtitlesFormat>,
+}
+
+impl SubtitlesOptions {
+    /// Build the YT-DLP arguments to download the subtitles
+    ///
+    /// YT-DLP always prefers regular subtitles over auto-generated ones for the same language.
+    pub fn to_yt_dlp_args(&self) -> Vec<String> {
+        let Self {
+            languages,
+            auto_generated,
+            embed,
+            format,
+        } = self;
+
+        let mut args = vec![
+            "--write-subs".to_owned(),
+            "--sub-langs".to_owned(),
+            languages.join(","),
+        ];
+
+        if *auto_generated {
+            args.push("--write-auto-subs".to_owned());
+        }
+
+        if let Some(format) = format {
+            args.push("--convert-subs".to_owned());
+            args.push(format.to_string());
+        }
+
+        if *embed {
+            args.push("--embed-subs".to_owned());
+        }
+
+        args
+    }
+}
+
+impl fmt::Display for SubtitlesOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            languages,
+            auto_generated,
+            embed,
+            format,
+        } = self;
+
+        write!(f, "{}", languages.join(","))?;
+
+        if *auto_generated {
+            write!(f, " (or auto-generated)")?;
+        }
+
+        if let Some(format) = format {
+            write!(f, " as {format}")?;
+        }
+
+        if *embed {
+            write!(f, ", embedded")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitlesFormat {
+    Srt,
+    Vtt,
+    Ass,
+}
+
+impl fmt::Display for SubtitlesFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Srt => "srt",
+                Self::Vtt => "vtt",
+                Self::Ass => "ass",
+            }
+        )
+    }
+}
+
+/// Check if a file is a subtitles file, judging by its extension
+pub fn is_subtitles_file(filename: &str) -> bool {
+    filename
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| SUBTITLES_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+static SUBTITLES_EXTENSIONS: &[&str] = &[
+    "srt", "vtt", "ass", "ssa", "ttml", "srv1", "srv2", "srv3", "json3", "lrc",
+];
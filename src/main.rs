@@ -18,9 +18,12 @@ use dirs::config_dir;
 use self::{
     cmd::{Action, Cmd},
     config::Config,
-    dl::{album::download_album, download_from_args},
+    dl::{album::download_album, cleanup::cleanup, download_from_args},
     sync::sync,
-    utils::{platforms::build_platform_matchers, ytdlp::check_version},
+    utils::{
+        interrupt::setup_interrupt_handler, platforms::build_platform_matchers,
+        ytdlp::check_version,
+    },
 };
 
 fn main() {
@@ -33,6 +36,8 @@ fn main() {
 fn inner_main() -> Result<()> {
     let args = Cmd::parse();
 
+    setup_interrupt_handler()?;
+
     let default_config_path = config_dir()
         .context("Failed to determine path to the configuration directory")?
         .join("ytdl")
@@ -72,9 +77,10 @@ fn inner_main() -> Result<()> {
     let cwd = env::current_dir().context("Failed to get current directory")?;
 
     match args.action {
-        Action::Dl(args) => download_from_args(args, &config, &build_platform_matchers(&config)?),
+        Action::Dl(args) => download_from_args(*args, &config, &build_platform_matchers(&config)?),
         Action::Sync(args) => sync(args, &config, &cwd),
         Action::Album(args) => download_album(args, &config, &cwd),
+        Action::Cleanup(args) => cleanup(args, &config),
         Action::InitConfig => Ok(()),
     }
 }
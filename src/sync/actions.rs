@@ -1,4 +1,10 @@
-use std::{fs, path::Path};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
@@ -8,14 +14,28 @@ use crate::{
     config::Config,
     dl::{download, SingleDlArgs},
     info, success,
-    sync::{blacklist::BlacklistEntry, builder::get_cache_path},
-    utils::platforms::build_platform_matchers,
+    sync::builder::{get_cache_path, parse_url_file},
+    utils::{interrupt::is_interrupted, platforms::build_platform_matchers, time::now_timestamp},
     warn,
 };
 
 use super::{
-    blacklist::blacklist_video, builder::build_or_update_cache, cmd::SyncAction,
-    display::display_sync, SyncArgs,
+    blacklist_actions::{blacklist, prune_auto_blacklists},
+    builder::build_or_update_cache,
+    cache::Cache,
+    cmd::SyncAction,
+    display::display_sync,
+    lock::SyncLock,
+    m3u::{export_m3u, save_playlists_order, write_m3u_playlists, M3uPlaylist},
+    mirror::{mirror, MirrorAction},
+    renumber::renumber,
+    retention::apply_retention,
+    settings::SyncSettings,
+    show_metadata::write_show_metadata,
+    state::SyncState,
+    status::status,
+    subtitles::backfill_subtitles,
+    SyncArgs,
 };
 
 pub fn sync(args: SyncArgs, config: &Config, sync_dir: &Path) -> Result<()> {
@@ -23,10 +43,32 @@ pub fn sync(args: SyncArgs, config: &Config, sync_dir: &Path) -> Result<()> {
 
     match action {
         SyncAction::Setup { url } => setup(&url, config, sync_dir),
-        SyncAction::Run { dry_run } => run(dry_run, config, sync_dir),
-        SyncAction::Blacklist { platform, video_id } => {
-            blacklist(BlacklistEntry::new(platform, video_id), config, sync_dir)
+        SyncAction::Run {
+            dry_run,
+            parallel,
+            yes,
+            wait,
+        } => run(dry_run, parallel, yes, wait, config, sync_dir),
+        SyncAction::Status { json } => status(json, config, sync_dir),
+        SyncAction::Blacklist(args) => blacklist(args, config, sync_dir),
+        SyncAction::Mirror {
+            move_files,
+            delete,
+            yes,
+        } => {
+            let action = if move_files {
+                MirrorAction::Move
+            } else if delete {
+                MirrorAction::Delete
+            } else {
+                MirrorAction::Report
+            };
+
+            mirror(action, yes, config, sync_dir)
         }
+        SyncAction::Renumber { dry_run } => renumber(dry_run, config, sync_dir),
+        SyncAction::ExportM3u => export_m3u(config, sync_dir),
+        SyncAction::Subtitles { dry_run } => backfill_subtitles(dry_run, config, sync_dir),
     }
 }
 
@@ -34,16 +76,17 @@ fn setup(url: &str, config: &Config, sync_dir: &Path) -> Result<()> {
     let sync_file = sync_dir.join(&config.url_filename);
 
     if sync_file.exists() {
-        let existing_url =
-            fs::read_to_string(&sync_file).context("Failed to read the synchronization file")?;
+        let existing_urls = parse_url_file(
+            &fs::read_to_string(&sync_file).context("Failed to read the synchronization file")?,
+        )?;
 
-        if existing_url == *url {
+        if existing_urls.iter().any(|existing| existing == url) {
             warn!("Provided URL is already specified in the synchronization file, doing nothing.");
             Ok(())
         } else {
             bail!(
-                "This directory already has a synchronization file with a different URL ({})",
-                existing_url.bright_cyan()
+                "This directory already has a synchronization file with a different URL ({}), edit it to add more URLs",
+                existing_urls.join(", ").bright_cyan()
             );
         }
     } else {
@@ -56,37 +99,39 @@ fn setup(url: &str, config: &Config, sync_dir: &Path) -> Result<()> {
     }
 }
 
-fn blacklist(entry: BlacklistEntry, config: &Config, sync_dir: &Path) -> Result<()> {
-    if !config.platforms.contains_key(entry.ie_key()) {
-        bail!(
-            "Unkonwn IE key '{}'. Registered platforms are: {}",
-            entry.ie_key(),
-            config
-                .platforms
-                .keys()
-                .map(String::as_str)
-                .collect::<Vec<_>>()
-                .join(", ")
-                .bright_cyan()
-        );
-    }
-
-    blacklist_video(&sync_dir.join(&config.custom_blacklist_filename), &entry)
-}
+fn run(
+    dry_run: bool,
+    parallel: Option<usize>,
+    yes: bool,
+    wait: bool,
+    config: &Config,
+    sync_dir: &Path,
+) -> Result<()> {
+    let _lock = SyncLock::acquire(sync_dir, wait, config)?;
 
-fn run(dry_run: bool, config: &Config, sync_dir: &Path) -> Result<()> {
     let cache_path = get_cache_path(sync_dir, config);
 
     let cache = build_or_update_cache(sync_dir, config, &cache_path)?;
 
-    display_sync(&cache);
+    let dirs_settings = cache
+        .entries
+        .iter()
+        .map(|entry| &entry.sync_dir)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .map(|dir| {
+            SyncSettings::load(&sync_dir.join(dir), config).map(|settings| (dir.clone(), settings))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    display_sync(&cache, &dirs_settings, config);
 
     if dry_run {
         info!("Dry run completed!");
         return Ok(());
     }
 
-    let entries = cache.entries;
+    let entries = &cache.entries;
 
     info!("");
     info!(
@@ -107,28 +152,21 @@ fn run(dry_run: bool, config: &Config, sync_dir: &Path) -> Result<()> {
 
     if entries.is_empty() {
         success!("Nothing to download!");
+        save_playlists_order(&cache.playlists_order, config, sync_dir)?;
         fs::remove_file(&cache_path)?;
-        return Ok(());
+        return finish_sync(&cache, config, sync_dir);
     }
 
     let platform_matchers = build_platform_matchers(config)?;
 
-    info!("");
-    info!("Do you want to continue?");
-
-    let ans = Confirm::new("Please confirm")
-        .with_default(true)
-        .prompt()
-        .context("Failed to setup or retrieve confirmation prompt")?;
-
-    info!("");
-
-    if !ans {
+    if !confirm_sync(entries.len(), yes, config)? {
         warn!("Aborting synchronization.");
         return Ok(());
     }
 
-    for entry in &entries {
+    save_playlists_order(&cache.playlists_order, config, sync_dir)?;
+
+    for entry in entries {
         if !config.platforms.contains_key(&entry.ie_key) {
             bail!(
                 "Found unregistered IE key '{}' in videos list (video title: {})",
@@ -139,22 +177,188 @@ fn run(dry_run: bool, config: &Config, sync_dir: &Path) -> Result<()> {
     }
 
     let dl_items = entries
-        .into_iter()
+        .iter()
         .map(|entry| {
-            (
-                entry.url,
-                SingleDlArgs {
-                    prefetched_title: Some(entry.title),
-                    output_dir: Some(entry.sync_dir),
-                    ..Default::default()
-                },
-            )
+            let settings = dirs_settings.get(&entry.sync_dir).expect(
+                "Internal consistency error: settings not found for given video's sync. directory",
+            );
+
+            let args = settings.dl_options.apply(SingleDlArgs {
+                prefetched_title: Some(entry.title.clone()),
+                output_dir: Some(entry.sync_dir.clone()),
+                position_in_playlist: entry.position,
+                ..Default::default()
+            });
+
+            if args.index_prefix == Some(true) && entry.position.is_none() {
+                bail!(
+                    "Cache was built by an older version and lacks the videos' position required for index prefixes, please remove it: {}",
+                    cache_path.to_string_lossy().bright_magenta()
+                );
+            }
+
+            Ok((entry.url.clone(), args))
         })
-        .collect::<Vec<_>>();
+        .collect::<Result<Vec<_>>>()?;
+
+    let dl_indexes = entries.iter().map(|entry| entry.index).collect::<Vec<_>>();
+
+    // Remove completed entries from the cache as we go, so an interrupted synchronization
+    // resumes exactly where it stopped
+    let cache = Mutex::new(cache);
+
+    // Each directory is only scanned once, then kept up-to-date with the downloaded files
+    let m3u_playlists = Mutex::new(HashMap::<PathBuf, Option<M3uPlaylist>>::new());
+
+    let on_completed = |item: usize, output_file: Option<&Path>| {
+        let mut cache = cache.lock().unwrap();
+
+        let Some(pos) = cache
+            .entries
+            .iter()
+            .position(|entry| entry.index == dl_indexes[item])
+        else {
+            return Ok(());
+        };
+
+        let entry = cache.entries.remove(pos);
+
+        // Keep the directory's playlist up-to-date, so it's usable even if the synchronization is interrupted
+        let m3u_result = update_m3u_playlist(
+            &mut m3u_playlists.lock().unwrap(),
+            &entry.sync_dir,
+            output_file,
+            config,
+            sync_dir,
+        )
+        .context("Failed to update M3U8 playlist");
+
+        cache
+            .save_to_disk(&cache_path)
+            .context("Failed to save synchronization progress to cache file")?;
+
+        m3u_result
+    };
+
+    let result = download(
+        &dl_items,
+        parallel,
+        config,
+        &platform_matchers,
+        Some(&on_completed),
+    );
+
+    if result.is_err() && is_interrupted() {
+        let remaining = cache.lock().unwrap().entries.len();
+
+        warn!(
+            "Synchronization was interrupted, {} videos remain to download. Run it again to resume.",
+            remaining.to_string().bright_yellow()
+        );
+    }
 
-    download(&dl_items, config, &platform_matchers)?;
+    result?;
 
     fs::remove_file(&cache_path).context("Failed to remove the cache file")?;
 
+    finish_sync(&cache.into_inner().unwrap(), config, sync_dir)
+}
+
+/// Update the M3U8 playlist of a directory after one of its videos was downloaded
+fn update_m3u_playlist(
+    m3u_playlists: &mut HashMap<PathBuf, Option<M3uPlaylist>>,
+    dir: &Path,
+    output_file: Option<&Path>,
+    config: &Config,
+    sync_dir: &Path,
+) -> Result<()> {
+    let playlist = match m3u_playlists.entry(dir.to_owned()) {
+        Entry::Occupied(entry) => {
+            let playlist = entry.into_mut();
+
+            // The directory only needs to be scanned again if the downloaded file's name isn't known
+            if let Some(playlist) = playlist {
+                match output_file.and_then(|file| file.file_name()?.to_str()) {
+                    Some(filename) => playlist.add_file(filename.to_owned()),
+                    None => playlist.rescan()?,
+                }
+            }
+
+            playlist
+        }
+
+        Entry::Vacant(entry) => entry.insert(M3uPlaylist::load(&sync_dir.join(dir), config)?),
+    };
+
+    let Some(playlist) = playlist else {
+        return Ok(());
+    };
+
+    playlist.write()?;
+
+    Ok(())
+}
+
+/// Maintain the synchronization directories once all their videos are downloaded
+fn finish_sync(cache: &Cache, config: &Config, sync_dir: &Path) -> Result<()> {
+    let playlists = &cache.playlists;
+
+    prune_auto_blacklists(playlists, config, sync_dir)?;
+    apply_retention(playlists, config, sync_dir)?;
+    write_m3u_playlists(playlists, config, sync_dir)?;
+    write_show_metadata(&cache.show_metadata, config, sync_dir)?;
+
+    mark_synced(playlists, config, sync_dir)
+}
+
+/// Ask for confirmation before downloading, unless it was already provided or isn't required
+fn confirm_sync(videos: usize, yes: bool, config: &Config) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+
+    let above_threshold = config
+        .sync_confirm_threshold
+        .is_none_or(|threshold| videos > threshold);
+
+    if !above_threshold {
+        return Ok(true);
+    }
+
+    if !io::stdin().is_terminal() {
+        return match config.sync_confirm_threshold {
+            None => Ok(true),
+            Some(threshold) => bail!(
+                "Refusing to download more than {} videos without confirmation, use {} to proceed anyway",
+                threshold.to_string().bright_yellow(),
+                "--yes".bright_cyan()
+            ),
+        };
+    }
+
+    info!("");
+    info!("Do you want to continue?");
+
+    let ans = Confirm::new("Please confirm")
+        .with_default(true)
+        .prompt()
+        .context("Failed to setup or retrieve confirmation prompt")?;
+
+    info!("");
+
+    Ok(ans)
+}
+
+fn mark_synced(playlists: &[PathBuf], config: &Config, sync_dir: &Path) -> Result<()> {
+    let now = now_timestamp();
+
+    for playlist in playlists {
+        let dir = sync_dir.join(playlist);
+
+        let mut state = SyncState::load(&dir, config)?;
+        state.last_sync = Some(now);
+        state.save(&dir, config)?;
+    }
+
     Ok(())
 }
@@ -1,80 +1,228 @@
 use std::{
+    fmt,
     fs::{self, OpenOptions},
     io::Write,
     path::Path,
+    str::FromStr,
 };
 
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
 
-pub struct Blacklist(pub Vec<BlacklistEntry>);
+use crate::utils::{
+    files::write_file_atomically,
+    ytdlp::{RawVideoInfos, YtDlpFailure},
+};
+
+use super::blacklist_rules::BlacklistRule;
+
+pub struct Blacklist {
+    pub entries: Vec<BlacklistEntry>,
+    pub rules: Vec<BlacklistRule>,
+}
 
 impl Blacklist {
     pub fn empty() -> Self {
-        Self(vec![])
+        Self::new(vec![], vec![])
     }
 
-    pub fn new(entries: Vec<BlacklistEntry>) -> Self {
-        Self(entries)
+    pub fn new(entries: Vec<BlacklistEntry>, rules: Vec<BlacklistRule>) -> Self {
+        Self { entries, rules }
     }
 
     pub fn decode(content: &str) -> Result<Self> {
-        Ok(Self(
-            content
-                .trim()
-                .lines()
-                .enumerate()
-                .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
-                .map(|(i, line)| {
+        let mut entries = vec![];
+        let mut rules = vec![];
+
+        for (i, line) in content.trim().lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('@') {
+                rules.push(
+                    BlacklistRule::decode(line)
+                        .with_context(|| format!("Failed to decode rule at line n°{}", i + 1))?,
+                );
+            } else {
+                entries.push(
                     BlacklistEntry::decode(line)
-                        .with_context(|| format!("Failed to decode line n°{}", i + 1))
-                })
-                .collect::<Result<Vec<_>>>()?,
-        ))
+                        .with_context(|| format!("Failed to decode line n°{}", i + 1))?,
+                );
+            }
+        }
+
+        Ok(Self::new(entries, rules))
     }
 
-    pub fn is_blacklisted(&self, ie_key: &str, video_id: &str) -> bool {
-        self.0
+    pub fn is_blacklisted(&self, video: &RawVideoInfos, video_id: &str) -> bool {
+        self.entries
             .iter()
-            .any(|entry| entry.ie_key == ie_key && entry.video_id == video_id)
+            .any(|entry| entry.ie_key == video.ie_key && entry.video_id == video_id)
+            || self.rules.iter().any(|rule| rule.matches(video))
     }
 }
 
+/// A blacklisted video
+///
+/// Encoded as `ie_key/video_id`, optionally followed by a space and its details as JSON
+/// (e.g. `Youtube/dQw4w9WgXcQ {"reason":"manual","added_at":1700000000}`).
 pub struct BlacklistEntry {
     ie_key: String,
     video_id: String,
+    details: BlacklistEntryDetails,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct BlacklistEntryDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<BlacklistReason>,
+
+    /// UNIX timestamp (in seconds) of when the entry was added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_at: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl BlacklistEntryDetails {
+    fn is_empty(&self) -> bool {
+        let Self {
+            reason,
+            added_at,
+            title,
+            comment,
+        } = self;
+
+        reason.is_none() && added_at.is_none() && title.is_none() && comment.is_none()
+    }
 }
 
 impl BlacklistEntry {
-    pub fn new(ie_key: String, video_id: String) -> Self {
-        Self { ie_key, video_id }
+    pub fn with_details(ie_key: String, video_id: String, details: BlacklistEntryDetails) -> Self {
+        Self {
+            ie_key,
+            video_id,
+            details,
+        }
     }
 
     pub fn decode(line: &str) -> Result<Self> {
-        let mut segments = line.split('/');
+        let (video, details) = match line.trim().split_once(' ') {
+            Some((video, details)) => (video, Some(details.trim())),
+            None => (line.trim(), None),
+        };
+
+        let mut segments = video.split('/');
 
         let ie_key = segments.next().context("IE key is missing")?.to_string();
         let id = segments.next().context("Video ID is missing")?.to_string();
 
         if segments.next().is_some() {
             bail!("Too many segments (/)")
-        } else {
-            Ok(Self {
-                ie_key,
-                video_id: id,
-            })
         }
+
+        let details = match details {
+            Some(details) => {
+                serde_json::from_str(details).context("Failed to decode the entry's details")?
+            }
+            None => BlacklistEntryDetails::default(),
+        };
+
+        Ok(Self {
+            ie_key,
+            video_id: id,
+            details,
+        })
     }
 
-    fn encode(&self) -> String {
-        let Self { ie_key, video_id } = self;
-        format!("{ie_key}/{video_id}")
+    fn encode(&self) -> Result<String> {
+        let Self {
+            ie_key,
+            video_id,
+            details,
+        } = self;
+
+        if details.is_empty() {
+            return Ok(format!("{ie_key}/{video_id}"));
+        }
+
+        let details = serde_json::to_string(details).context("Failed to encode entry's details")?;
+
+        Ok(format!("{ie_key}/{video_id} {details}"))
     }
 
     pub fn ie_key(&self) -> &str {
         &self.ie_key
     }
+
+    pub fn video_id(&self) -> &str {
+        &self.video_id
+    }
+
+    pub fn details(&self) -> &BlacklistEntryDetails {
+        &self.details
+    }
+}
+
+/// Why a video was blacklisted
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BlacklistReason {
+    /// Blacklisted by the user
+    Manual,
+
+    /// Automatically blacklisted as the video is unavailable
+    Unavailable(YtDlpFailure),
+
+    /// Removed by the directory's retention policy
+    Retention,
+}
+
+impl fmt::Display for BlacklistReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Manual => write!(f, "manual"),
+            Self::Unavailable(failure) => write!(f, "unavailable:{failure}"),
+            Self::Retention => write!(f, "retention"),
+        }
+    }
+}
+
+impl FromStr for BlacklistReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manual" => return Ok(Self::Manual),
+            "retention" => return Ok(Self::Retention),
+            _ => {}
+        }
+
+        match s.strip_prefix("unavailable:") {
+            Some(failure) => failure.parse().map(Self::Unavailable),
+            None => Err(format!("Unknown blacklist reason: {s}")),
+        }
+    }
+}
+
+impl Serialize for BlacklistReason {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for BlacklistReason {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 pub fn load_blacklist_file(path: &Path) -> Result<Blacklist> {
@@ -105,12 +253,14 @@ pub fn load_optional_blacklists(paths: &[&Path]) -> Result<Blacklist> {
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    Ok(Blacklist::new(
-        blacklists
-            .into_iter()
-            .flat_map(|blacklist| blacklist.0)
-            .collect(),
-    ))
+    let mut merged = Blacklist::empty();
+
+    for blacklist in blacklists {
+        merged.entries.extend(blacklist.entries);
+        merged.rules.extend(blacklist.rules);
+    }
+
+    Ok(merged)
 }
 
 pub fn blacklist_video(path: &Path, entry: &BlacklistEntry) -> Result<()> {
@@ -120,5 +270,57 @@ pub fn blacklist_video(path: &Path, entry: &BlacklistEntry) -> Result<()> {
         .open(path)
         .context("Failed to create or open blacklist file")?;
 
-    writeln!(file, "{}", entry.encode()).context("Failed to update blacklist file")
+    writeln!(file, "{}", entry.encode()?).context("Failed to update blacklist file")
+}
+
+/// Remove the entries matching a predicate from a blacklist file, leaving the other lines untouched
+///
+/// Returns the removed entries. The file is left unchanged in dry-run mode.
+pub fn remove_from_blacklist(
+    path: &Path,
+    should_remove: impl Fn(&BlacklistEntry) -> bool,
+    dry_run: bool,
+) -> Result<Vec<BlacklistEntry>> {
+    let content = fs::read_to_string(path).with_context(|| {
+        format!(
+            "Failed to read blacklist file at path '{}'",
+            path.to_string_lossy().bright_magenta()
+        )
+    })?;
+
+    let mut kept = vec![];
+    let mut removed = vec![];
+
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') || line.starts_with('@') {
+            kept.push(line);
+            continue;
+        }
+
+        let entry = BlacklistEntry::decode(line).with_context(|| {
+            format!(
+                "Failed to decode line n°{} of blacklist {}",
+                i + 1,
+                path.to_string_lossy().bright_magenta()
+            )
+        })?;
+
+        if should_remove(&entry) {
+            removed.push(entry);
+        } else {
+            kept.push(line);
+        }
+    }
+
+    if !removed.is_empty() && !dry_run {
+        let mut content = kept.join("\n");
+
+        if !content.is_empty() {
+            content.push('\n');
+        }
+
+        write_file_atomically(path, &content).context("Failed to update blacklist file")?;
+    }
+
+    Ok(removed)
 }
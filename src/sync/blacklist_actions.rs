@@ -0,0 +1,388 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Result, bail};
+use colored::Colorize;
+
+use crate::{
+    config::Config,
+    info, success,
+    utils::time::{SECS_PER_DAY, format_timestamp, now_timestamp},
+    warn,
+};
+
+use super::{
+    blacklist::{
+        Blacklist, BlacklistEntry, BlacklistEntryDetails, BlacklistReason, blacklist_video,
+        load_blacklist_file, remove_from_blacklist,
+    },
+    builder::find_playlists,
+    cmd::{BlacklistAction, BlacklistAddArgs, BlacklistArgs},
+};
+
+pub fn blacklist(args: BlacklistArgs, config: &Config, sync_dir: &Path) -> Result<()> {
+    let BlacklistArgs { action, add } = args;
+
+    match action.unwrap_or(BlacklistAction::Add(add)) {
+        BlacklistAction::Add(args) => add_entry(args, config, sync_dir),
+        BlacklistAction::List { auto, custom } => list(auto, custom, config, sync_dir),
+        BlacklistAction::Search { query } => search(&query, config, sync_dir),
+        BlacklistAction::Remove { platform, video_id } => {
+            remove(&platform, &video_id, config, sync_dir)
+        }
+        BlacklistAction::Prune {
+            older_than,
+            dry_run,
+        } => prune(older_than, dry_run, config, sync_dir),
+    }
+}
+
+fn add_entry(args: BlacklistAddArgs, config: &Config, sync_dir: &Path) -> Result<()> {
+    let BlacklistAddArgs {
+        platform,
+        video_id,
+        comment,
+    } = args;
+
+    // Both arguments are required by the command-line parser
+    let (Some(platform), Some(video_id)) = (platform, video_id) else {
+        unreachable!()
+    };
+
+    if !config.platforms.contains_key(&platform) {
+        bail!(
+            "Unkonwn IE key '{}'. Registered platforms are: {}",
+            platform,
+            config
+                .platforms
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+                .bright_cyan()
+        );
+    }
+
+    blacklist_video(
+        &sync_dir.join(&config.custom_blacklist_filename),
+        &BlacklistEntry::with_details(
+            platform,
+            video_id,
+            BlacklistEntryDetails {
+                reason: Some(BlacklistReason::Manual),
+                added_at: Some(now_timestamp()),
+                title: None,
+                comment,
+            },
+        ),
+    )
+}
+
+fn list(auto: bool, custom: bool, config: &Config, sync_dir: &Path) -> Result<()> {
+    let files = find_blacklist_files(config, sync_dir)?
+        .into_iter()
+        .filter(|file| match file.kind {
+            BlacklistKind::Auto => !custom,
+            BlacklistKind::Custom => !auto,
+        })
+        .collect::<Vec<_>>();
+
+    display_matching_entries(&files, true, |_| true)
+}
+
+fn search(query: &str, config: &Config, sync_dir: &Path) -> Result<()> {
+    let query = query.to_lowercase();
+
+    let files = find_blacklist_files(config, sync_dir)?;
+
+    display_matching_entries(&files, false, |entry| {
+        let BlacklistEntryDetails {
+            reason: _,
+            added_at: _,
+            title,
+            comment,
+        } = entry.details();
+
+        [Some(entry.video_id()), title.as_deref(), comment.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(|field| field.to_lowercase().contains(&query))
+    })
+}
+
+fn remove(platform: &str, video_id: &str, config: &Config, sync_dir: &Path) -> Result<()> {
+    let mut removed = 0;
+
+    for file in find_blacklist_files(config, sync_dir)? {
+        let entries = remove_from_blacklist(
+            &file.path,
+            |entry| entry.ie_key() == platform && entry.video_id() == video_id,
+            false,
+        )?;
+
+        if !entries.is_empty() {
+            info!(
+                "Removed from {} blacklist of {}",
+                file.kind.name(),
+                file.sync_dir.to_string_lossy().bright_cyan()
+            );
+        }
+
+        removed += entries.len();
+    }
+
+    if removed == 0 {
+        warn!("Video was not found in any blacklist.");
+    } else {
+        success!(
+            "Removed {} blacklist entries.",
+            removed.to_string().bright_yellow()
+        );
+    }
+
+    Ok(())
+}
+
+fn prune(
+    older_than: Option<Duration>,
+    dry_run: bool,
+    config: &Config,
+    sync_dir: &Path,
+) -> Result<()> {
+    let Some(max_age) = older_than.or(auto_blacklist_expiry(config)) else {
+        bail!(
+            "No expiry provided, use {} or set {} in the configuration file",
+            "--older-than".bright_cyan(),
+            "auto_blacklist_expiry_days".bright_cyan()
+        );
+    };
+
+    let mut pruned = 0;
+
+    for file in find_blacklist_files(config, sync_dir)? {
+        if file.kind != BlacklistKind::Auto {
+            continue;
+        }
+
+        let entries = remove_expired_entries(&file.path, max_age, dry_run)?;
+
+        if !entries.is_empty() {
+            info!("{}:", file.sync_dir.to_string_lossy().bright_cyan());
+
+            for entry in &entries {
+                display_entry(entry);
+            }
+        }
+
+        pruned += entries.len();
+    }
+
+    if dry_run {
+        info!(
+            "Found {} expired entries.",
+            pruned.to_string().bright_yellow()
+        );
+    } else {
+        success!(
+            "Removed {} expired entries.",
+            pruned.to_string().bright_yellow()
+        );
+    }
+
+    Ok(())
+}
+
+/// Expiry of the automatic blacklists' entries, as set in the configuration
+pub fn auto_blacklist_expiry(config: &Config) -> Option<Duration> {
+    config
+        .auto_blacklist_expiry_days
+        .map(|days| Duration::from_secs(days * SECS_PER_DAY))
+}
+
+/// Remove the entries older than the provided age from a blacklist file
+pub fn remove_expired_entries(
+    path: &Path,
+    max_age: Duration,
+    dry_run: bool,
+) -> Result<Vec<BlacklistEntry>> {
+    let now = now_timestamp();
+
+    remove_from_blacklist(path, |entry| is_expired(entry, max_age, now), dry_run)
+}
+
+/// Check if a blacklist entry is older than the provided age
+///
+/// Entries without a date (e.g. written by older versions) never expire, nor do the ones
+/// of videos removed by a retention policy.
+pub fn is_expired(entry: &BlacklistEntry, max_age: Duration, now: u64) -> bool {
+    let BlacklistEntryDetails {
+        reason,
+        added_at,
+        title: _,
+        comment: _,
+    } = entry.details();
+
+    *reason != Some(BlacklistReason::Retention)
+        && added_at.is_some_and(|added_at| now.saturating_sub(added_at) > max_age.as_secs())
+}
+
+/// Remove the expired entries from the automatic blacklists of synchronization directories
+pub fn prune_auto_blacklists(dirs: &[PathBuf], config: &Config, sync_dir: &Path) -> Result<()> {
+    let Some(max_age) = auto_blacklist_expiry(config) else {
+        return Ok(());
+    };
+
+    for dir in dirs {
+        let path = sync_dir.join(dir).join(&config.auto_blacklist_filename);
+
+        if !path.is_file() {
+            continue;
+        }
+
+        let expired = remove_expired_entries(&path, max_age, false)?;
+
+        if !expired.is_empty() {
+            info!(
+                "Removed {} expired entries from the automatic blacklist of {}",
+                expired.len().to_string().bright_yellow(),
+                dir.to_string_lossy().bright_cyan()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BlacklistKind {
+    Auto,
+    Custom,
+}
+
+impl BlacklistKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "automatic",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+struct BlacklistFile {
+    sync_dir: PathBuf,
+    kind: BlacklistKind,
+    path: PathBuf,
+}
+
+/// Find the existing blacklist files of all synchronization directories in the tree
+fn find_blacklist_files(config: &Config, sync_dir: &Path) -> Result<Vec<BlacklistFile>> {
+    let mut playlists = find_playlists(sync_dir, config)?;
+    playlists.sort_by_key(|playlist| playlist.sync_dir.to_string_lossy().to_lowercase());
+
+    let mut files = vec![];
+
+    for playlist in playlists {
+        for (kind, filename) in [
+            (BlacklistKind::Auto, &config.auto_blacklist_filename),
+            (BlacklistKind::Custom, &config.custom_blacklist_filename),
+        ] {
+            let path = sync_dir.join(&playlist.sync_dir).join(filename);
+
+            if path.is_file() {
+                files.push(BlacklistFile {
+                    sync_dir: playlist.sync_dir.clone(),
+                    kind,
+                    path,
+                });
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+fn display_matching_entries(
+    files: &[BlacklistFile],
+    show_rules: bool,
+    filter: impl Fn(&BlacklistEntry) -> bool,
+) -> Result<()> {
+    let mut total = 0;
+
+    for file in files {
+        let Blacklist { entries, rules } = load_blacklist_file(&file.path)?;
+
+        let entries = entries.into_iter().filter(&filter).collect::<Vec<_>>();
+        let rules = if show_rules { rules } else { vec![] };
+
+        if entries.is_empty() && rules.is_empty() {
+            continue;
+        }
+
+        let rules_count = if rules.is_empty() {
+            String::new()
+        } else {
+            format!(", {} rules", rules.len().to_string().bright_yellow())
+        };
+
+        info!(
+            "{} ({} blacklist, {} entries{}):",
+            file.sync_dir.to_string_lossy().bright_cyan(),
+            file.kind.name(),
+            entries.len().to_string().bright_yellow(),
+            rules_count
+        );
+
+        for rule in &rules {
+            println!("  {}", rule.line().bright_green());
+        }
+
+        for entry in &entries {
+            display_entry(entry);
+        }
+
+        info!("");
+
+        total += entries.len() + rules.len();
+    }
+
+    if total == 0 {
+        info!("No blacklisted video found.");
+    }
+
+    Ok(())
+}
+
+fn display_entry(entry: &BlacklistEntry) {
+    let BlacklistEntryDetails {
+        reason,
+        added_at,
+        title,
+        comment,
+    } = entry.details();
+
+    let mut line = format!(
+        "  {}/{}",
+        entry.ie_key().bright_blue(),
+        entry.video_id().bright_yellow()
+    );
+
+    if let Some(reason) = reason {
+        line.push_str(&format!(" [{}]", reason.to_string().bright_red()));
+    }
+
+    if let Some(added_at) = added_at {
+        line.push_str(&format!(" {}", format_timestamp(*added_at).bright_black()));
+    }
+
+    if let Some(title) = title {
+        line.push_str(&format!(" {}", title.bright_magenta()));
+    }
+
+    if let Some(comment) = comment {
+        line.push_str(&format!(" ({})", comment.italic()));
+    }
+
+    println!("{line}");
+}
@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+
+use crate::utils::{
+    regex::compile_pomsky,
+    time::{parse_duration, parse_upload_date},
+    ytdlp::RawVideoInfos,
+};
+
+/// Rule blacklisting all videos matching a condition
+///
+/// Encoded as `@<condition> <value>`, e.g. `@title "#shorts"`, `@duration ..60s`,
+/// `@upload-date 2020-01-01..2020-12-31` or `@uploader Some Channel`.
+pub struct BlacklistRule {
+    line: String,
+    condition: RuleCondition,
+}
+
+enum RuleCondition {
+    /// Pomsky expression matched against the title
+    Title(Regex),
+
+    /// Inclusive range of durations
+    Duration {
+        min: Option<Duration>,
+        max: Option<Duration>,
+    },
+
+    /// Inclusive range of upload dates, as `YYYYMMDD`
+    UploadDate {
+        from: Option<String>,
+        to: Option<String>,
+    },
+
+    /// Name of the uploader or channel (case-insensitive)
+    Uploader(String),
+}
+
+impl BlacklistRule {
+    pub fn decode(line: &str) -> Result<Self> {
+        let rule = line
+            .trim()
+            .strip_prefix('@')
+            .context("Rules must start with '@'")?;
+
+        let (name, value) = rule.split_once(' ').context("Rule is missing a value")?;
+
+        let value = value.trim();
+
+        let condition = match name {
+            "title" => RuleCondition::Title(compile_pomsky(value)?),
+
+            "duration" => {
+                let (min, max) = parse_range(value, |bound| {
+                    parse_duration(bound).map_err(anyhow::Error::msg)
+                })?;
+
+                RuleCondition::Duration { min, max }
+            }
+
+            "upload-date" => {
+                let (from, to) = parse_range(value, |bound| {
+                    parse_upload_date(bound).map_err(anyhow::Error::msg)
+                })?;
+                RuleCondition::UploadDate { from, to }
+            }
+
+            "uploader" => RuleCondition::Uploader(value.to_lowercase()),
+
+            _ => bail!(
+                "Unknown rule '{name}' (expected 'title', 'duration', 'upload-date' or 'uploader')"
+            ),
+        };
+
+        Ok(Self {
+            line: line.trim().to_owned(),
+            condition,
+        })
+    }
+
+    /// Check if a video matches the rule
+    ///
+    /// Videos missing the information required by the rule never match.
+    pub fn matches(&self, video: &RawVideoInfos) -> bool {
+        match &self.condition {
+            RuleCondition::Title(regex) => regex.is_match(&video.title),
+
+            RuleCondition::Duration { min, max } => video.duration.is_some_and(|duration| {
+                let duration = Duration::from_secs_f64(duration.max(0.0));
+
+                min.is_none_or(|min| duration >= min) && max.is_none_or(|max| duration <= max)
+            }),
+
+            RuleCondition::UploadDate { from, to } => {
+                video.upload_date.as_deref().is_some_and(|date| {
+                    from.as_deref().is_none_or(|from| date >= from)
+                        && to.as_deref().is_none_or(|to| date <= to)
+                })
+            }
+
+            RuleCondition::Uploader(name) => [&video.uploader, &video.channel]
+                .into_iter()
+                .flatten()
+                .any(|uploader| uploader.to_lowercase() == *name),
+        }
+    }
+
+    pub fn line(&self) -> &str {
+        &self.line
+    }
+}
+
+/// Parse a range made of two optional bounds separated by `..` (e.g. `..60s`, `1h..` or `1m..10m`)
+fn parse_range<T>(
+    input: &str,
+    parse_bound: impl Fn(&str) -> Result<T>,
+) -> Result<(Option<T>, Option<T>)> {
+    let (start, end) = input
+        .split_once("..")
+        .context("Range must contain '..' between its bounds")?;
+
+    let parse = |bound: &str| -> Result<Option<T>> {
+        let bound = bound.trim();
+
+        if bound.is_empty() {
+            Ok(None)
+        } else {
+            parse_bound(bound)
+                .with_context(|| format!("Invalid range bound '{bound}'"))
+                .map(Some)
+        }
+    };
+
+    Ok((parse(start)?, parse(end)?))
+}
@@ -1,7 +1,6 @@
 use anyhow::{Context, Result, bail};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
-use pomsky_macro::pomsky;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use regex::Regex;
 use std::{
@@ -18,28 +17,44 @@ use walkdir::WalkDir;
 
 use super::{
     blacklist::{Blacklist, blacklist_video, load_optional_blacklists},
+    blacklist_actions::{auto_blacklist_expiry, is_expired},
     cache::{Cache, CacheEntry, PlatformVideo},
+    index::VideoIndex,
+    m3u::playlists_order,
+    settings::SyncSettings,
+    show_metadata::find_show_metadata_targets,
 };
 
 use crate::{
     config::Config,
+    dl::PositionInPlaylist,
     error, info, info_inline, success,
-    sync::blacklist::BlacklistEntry,
+    sync::blacklist::{BlacklistEntry, BlacklistEntryDetails, BlacklistReason},
     utils::{
+        files::MEDIA_EXTENSIONS,
+        interrupt::is_interrupted,
         platforms::{
             FoundPlatform, ID_REGEX_MATCHING_GROUP_NAME, build_platform_matchers,
             determine_video_id, find_platform,
         },
-        ytdlp::{check_availability, fetch_playlist},
+        regex::compile_pomsky,
+        time::now_timestamp,
+        ytdlp::{RawPlaylistInfos, check_availability, fetch_playlist},
     },
     warn,
 };
 
 pub static VIDEO_ID_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(pomsky!(
-        let ext = "mp4"|  "mkv"|  "webm"|  "mov"|  "avi"|  "mp3"|  "ogg"|  "flac"|  "alac"|  "aac"|  "3gp"|  "wav"|  "aiff"|  "dsf";
-        " [" :id(['a'-'z' 'A'-'Z' '0'-'9' '_' '-']+) "]." ext End
-    )).unwrap()
+    let ext = MEDIA_EXTENSIONS
+        .iter()
+        .map(|ext| format!("'{ext}'"))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    compile_pomsky(&format!(
+        "\" [\" :id(['a'-'z' 'A'-'Z' '0'-'9' '_' '-']+) \"].\" ({ext}) End"
+    ))
+    .unwrap()
 });
 
 pub fn get_cache_path(sync_dir: &Path, config: &Config) -> PathBuf {
@@ -55,9 +70,19 @@ pub fn build_or_update_cache(sync_dir: &Path, config: &Config, cache_path: &Path
 
     let old_cache = Cache::load_from_disk(cache_path)?;
 
+    // Caches built by older versions don't list the synchronization directories they were built from,
+    // which are required to record the synchronization once all videos are downloaded
+    if old_cache.playlists.is_empty() {
+        warn!("Cache was built by an older version, rebuilding it...");
+
+        let cache = build_cache(sync_dir, config)?;
+        cache.save_to_disk(cache_path)?;
+        return Ok(cache);
+    }
+
     let old_cache_entries = old_cache.entries.len();
 
-    let updated_cache = remove_downloaded_entries(old_cache)?;
+    let updated_cache = remove_downloaded_entries(old_cache, config)?;
 
     if updated_cache.entries.len() == old_cache_entries {
         info!("Successfully checked cache, nothing to update.");
@@ -86,15 +111,31 @@ fn build_cache(sync_dir: &Path, config: &Config) -> Result<Cache> {
 
     let sync_dirs: HashSet<_> = playlists.iter().map(|p| p.sync_dir.clone()).collect();
 
+    let now = now_timestamp();
+
     // Decode blacklists beforehand to ensure there won't be an error that will make the whole program fail
     // after all playlists have been fetched.
     let blacklists = sync_dirs
         .iter()
         .map(|dir| -> Result<(&PathBuf, Blacklist)> {
-            let merged_blacklists = load_optional_blacklists(&[
-                &sync_dir.join(dir).join(&config.auto_blacklist_filename),
-                &sync_dir.join(dir).join(&config.custom_blacklist_filename),
-            ])?;
+            let dir_path = sync_dir.join(dir);
+
+            let mut merged_blacklists =
+                load_optional_blacklists(&[&dir_path.join(&config.auto_blacklist_filename)])?;
+
+            // Expired entries are ignored so the related videos are checked again,
+            // they are only removed from the file once the synchronization is done
+            if let Some(max_age) = auto_blacklist_expiry(config) {
+                merged_blacklists
+                    .entries
+                    .retain(|entry| !is_expired(entry, max_age, now));
+            }
+
+            let custom_blacklist =
+                load_optional_blacklists(&[&dir_path.join(&config.custom_blacklist_filename)])?;
+
+            merged_blacklists.entries.extend(custom_blacklist.entries);
+            merged_blacklists.rules.extend(custom_blacklist.rules);
 
             Ok((dir, merged_blacklists))
         })
@@ -102,18 +143,31 @@ fn build_cache(sync_dir: &Path, config: &Config) -> Result<Cache> {
 
     // Build directory indexes beforehand to ensure there won't be an error that will make the whole program fail
     // after all playlists have been fetched.
-    let indexes = build_approximate_indexes(&sync_dirs)?;
+    let indexes = build_approximate_indexes(&sync_dirs, config)?;
 
-    let videos = fetch_playlists(playlists, config)?;
+    // Determined beforehand as the playlists are consumed when fetching them
+    let mut show_metadata = find_show_metadata_targets(&playlists, config)?;
+
+    let FetchedPlaylists {
+        videos,
+        mut infos,
+        with_empty_playlist: _,
+    } = fetch_playlists_detailed(playlists, true, config)?;
+
+    for (dir, target) in &mut show_metadata {
+        target.infos = infos.remove(dir);
+    }
 
     info!("Found a total of {} videos.", videos.len());
 
+    let playlists_order = playlists_order(&sync_dirs, &videos);
+
     let videos = videos.into_iter().filter(|video| {
         let blacklist = blacklists
             .get(&video.sync_dir)
             .expect("Internal consistency error: blacklist not found for given video");
 
-        !blacklist.is_blacklisted(&video.raw.ie_key, &video.id)
+        !blacklist.is_blacklisted(&video.raw, &video.id)
     });
 
     let videos: Vec<_> = videos
@@ -133,10 +187,18 @@ fn build_cache(sync_dir: &Path, config: &Config) -> Result<Cache> {
         .map(CacheEntry::from)
         .collect::<Vec<_>>();
 
-    Ok(Cache::new(entries))
+    let mut playlists = sync_dirs.into_iter().collect::<Vec<_>>();
+    playlists.sort();
+
+    Ok(Cache::new(
+        entries,
+        playlists,
+        playlists_order,
+        show_metadata,
+    ))
 }
 
-fn find_playlists(sync_dir: &Path, config: &Config) -> Result<Vec<PlaylistUrl>> {
+pub fn find_playlists(sync_dir: &Path, config: &Config) -> Result<Vec<PlaylistUrl>> {
     let mut playlists = vec![];
 
     let sync_dir =
@@ -148,13 +210,20 @@ fn find_playlists(sync_dir: &Path, config: &Config) -> Result<Vec<PlaylistUrl>>
         if let Some(name) = item.file_name().to_str()
             && name == config.url_filename
         {
-            let url = fs::read_to_string(item.path()).with_context(|| {
+            let content = fs::read_to_string(item.path()).with_context(|| {
                 format!(
                     "Failed to read playlist file at path {}",
                     item.path().to_string_lossy().bright_magenta()
                 )
             })?;
 
+            let urls = parse_url_file(&content).with_context(|| {
+                format!(
+                    "Failed to parse playlist file at path {}",
+                    item.path().to_string_lossy().bright_magenta()
+                )
+            })?;
+
             let path = fs::canonicalize(item.path().parent().unwrap_or_else(|| Path::new("")))
                 .context("Failed to canonicalize synchronization directory")?;
 
@@ -167,7 +236,8 @@ fn find_playlists(sync_dir: &Path, config: &Config) -> Result<Vec<PlaylistUrl>>
 
             playlists.push(PlaylistUrl {
                 sync_dir: relative_path.to_path_buf(),
-                url: url.trim().to_string(),
+                urls,
+                settings: SyncSettings::load(&path, config)?,
             });
         }
     }
@@ -179,21 +249,76 @@ fn find_playlists(sync_dir: &Path, config: &Config) -> Result<Vec<PlaylistUrl>>
     Ok(playlists)
 }
 
-fn fetch_playlists(playlists: Vec<PlaylistUrl>, config: &Config) -> Result<Vec<PlatformVideo>> {
+/// Parse the content of a playlist file
+///
+/// Each line contains a URL, optionally followed by a comment starting with a `#`.
+/// Empty lines and lines starting with a `#` are ignored.
+pub fn parse_url_file(content: &str) -> Result<Vec<String>> {
+    let mut urls = vec![];
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (url, rest) = match line.split_once(char::is_whitespace) {
+            Some((url, rest)) => (url, rest.trim_start()),
+            None => (line, ""),
+        };
+
+        if !rest.is_empty() && !rest.starts_with('#') {
+            bail!(
+                "Unexpected content after URL at line n°{} (comments must start with a '#')",
+                i + 1
+            );
+        }
+
+        urls.push(url.to_owned());
+    }
+
+    if urls.is_empty() {
+        bail!("No URL found in playlist file");
+    }
+
+    Ok(urls)
+}
+
+/// Fetch the videos of all playlists, optionally applying the directories' filters
+///
+/// The videos' position is determined before filtering, so it stays the same if the filters change.
+pub fn fetch_playlists(
+    playlists: Vec<PlaylistUrl>,
+    apply_filters: bool,
+    config: &Config,
+) -> Result<Vec<PlatformVideo>> {
+    fetch_playlists_detailed(playlists, apply_filters, config).map(|fetched| fetched.videos)
+}
+
+/// Fetch the videos of all playlists like [`fetch_playlists`], along with informations about each directory
+pub fn fetch_playlists_detailed(
+    mut playlists: Vec<PlaylistUrl>,
+    apply_filters: bool,
+    config: &Config,
+) -> Result<FetchedPlaylists> {
     let platform_matchers = build_platform_matchers(config)?;
 
     let mut parallel_fetching = true;
 
-    for playlist in &playlists {
+    for url in playlists.iter().flat_map(|playlist| &playlist.urls) {
         let FoundPlatform {
             platform_config,
             is_playlist,
             platform_name: _,
             platform_matchers: _,
-        } = find_platform(&playlist.url, config, &platform_matchers)?;
+        } = find_platform(url, config, &platform_matchers)?;
 
         if !is_playlist {
-            bail!("Provided URL is a video, not a playlist!");
+            bail!(
+                "Provided URL is a video, not a playlist: {}",
+                url.bright_magenta()
+            );
         }
 
         if platform_config.dl_options.rate_limited == Some(true) {
@@ -201,13 +326,20 @@ fn fetch_playlists(playlists: Vec<PlaylistUrl>, config: &Config) -> Result<Vec<P
         }
     }
 
+    playlists.sort_by_key(|playlist| playlist.sync_dir.to_string_lossy().to_lowercase());
+
+    let urls = playlists
+        .iter()
+        .flat_map(|playlist| &playlist.urls)
+        .collect::<Vec<_>>();
+
     if !parallel_fetching {
         warn!(
             "Detected at least one platform with rate limiting, fetching playlists sequentially."
         );
     }
 
-    let pb = ProgressBar::new(playlists.len() as u64).with_style(
+    let pb = ProgressBar::new(urls.len() as u64).with_style(
         ProgressStyle::default_bar()
             .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos:>3}/{len:3} {eta_precise} {msg}")
             .expect("Invalid template provided for ProgressBar")
@@ -217,10 +349,9 @@ fn fetch_playlists(playlists: Vec<PlaylistUrl>, config: &Config) -> Result<Vec<P
     pb.set_message("Starting to fetch...");
     pb.enable_steady_tick(Duration::from_secs(100));
 
-    let remaining = AtomicUsize::new(playlists.len());
-    let playlist_fetcher = |p: PlaylistUrl| {
-        let playlist =
-            fetch_playlist(&config.yt_dlp_bin, &p.url, None).map(|playlist| (p.sync_dir, playlist));
+    let remaining = AtomicUsize::new(urls.len());
+    let playlist_fetcher = |url: &String| {
+        let playlist = fetch_playlist(&config.yt_dlp_bin, url, None);
 
         let rem = remaining.fetch_sub(1, Ordering::SeqCst) - 1;
 
@@ -234,70 +365,181 @@ fn fetch_playlists(playlists: Vec<PlaylistUrl>, config: &Config) -> Result<Vec<P
         playlist
     };
 
-    let mut playlists_content = if parallel_fetching {
-        playlists
-            .into_par_iter()
+    // Results are in the same order as the URLs
+    let playlists_content = if parallel_fetching {
+        urls.into_par_iter()
             .map(playlist_fetcher)
             .collect::<Result<Vec<_>, _>>()?
     } else {
-        playlists
-            .into_iter()
+        urls.into_iter()
             .map(playlist_fetcher)
             .collect::<Result<Vec<_>, _>>()?
     };
 
     pb.finish_with_message("Done!");
 
-    playlists_content.sort_by(|a, b| {
-        a.0.to_string_lossy()
-            .to_lowercase()
-            .cmp(&b.0.to_string_lossy().to_lowercase())
-    });
-
     let total_videos = playlists_content
         .iter()
-        .map(|(_, playlist)| playlist.entries.len())
+        .map(|playlist| playlist.entries.len())
         .sum();
 
+    let mut playlists_content = playlists_content.into_iter();
+
     let mut entries = Vec::with_capacity(total_videos);
+    let mut infos = HashMap::new();
+    let mut with_empty_playlist = HashSet::new();
+
+    for PlaylistUrl {
+        sync_dir: path,
+        urls,
+        settings,
+    } in playlists
+    {
+        let mut videos = vec![];
+
+        // Videos present in multiple playlists of the same directory are only kept once
+        let mut seen = HashSet::new();
+        let mut duplicates = 0;
+
+        for (i, playlist) in playlists_content.by_ref().take(urls.len()).enumerate() {
+            if i == 0 {
+                infos.insert(path.clone(), playlist.infos);
+            }
 
-    for (path, playlist) in playlists_content {
-        for video in playlist.entries {
-            let platform = config.platforms.get(&video.ie_key).with_context(|| {
-                format!(
-                    "Found unregistered platform (IE key) {} for video at URL {}",
-                    video.ie_key.bright_yellow(),
-                    video.url.bright_magenta()
-                )
-            })?;
+            if playlist.entries.is_empty() {
+                with_empty_playlist.insert(path.clone());
+            }
 
-            let id = determine_video_id(&video, &platform_matchers).with_context(|| {
-                format!(
-                    "Failed to determine video ID for video at URL {}",
-                    video.url.bright_magenta()
-                )
-            })?;
+            for video in playlist.entries {
+                let platform = config.platforms.get(&video.ie_key).with_context(|| {
+                    format!(
+                        "Found unregistered platform (IE key) {} for video at URL {}",
+                        video.ie_key.bright_yellow(),
+                        video.url.bright_magenta()
+                    )
+                })?;
+
+                let id = determine_video_id(&video, &platform_matchers).with_context(|| {
+                    format!(
+                        "Failed to determine video ID for video at URL {}",
+                        video.url.bright_magenta()
+                    )
+                })?;
+
+                if !seen.insert((video.ie_key.clone(), id.clone())) {
+                    duplicates += 1;
+                    continue;
+                }
+
+                videos.push(PlatformVideo {
+                    id,
+                    raw: video,
+                    sync_dir: path.clone(),
+                    position: PositionInPlaylist {
+                        index: videos.len(),
+                        total: 0,
+                    },
+                    needs_checking: settings
+                        .dl_options
+                        .needs_checking
+                        .or(platform.dl_options.needs_checking)
+                        == Some(true),
+                });
+            }
+        }
 
-            entries.push(PlatformVideo {
-                id,
-                raw: video,
-                sync_dir: path.clone(),
-                needs_checking: platform.dl_options.needs_checking == Some(true),
-            });
+        let total = videos.len();
+
+        for video in &mut videos {
+            video.position.total = total;
+        }
+
+        if !apply_filters {
+            entries.extend(videos);
+            continue;
+        }
+
+        if duplicates > 0 {
+            info!(
+                "Ignored {} duplicate videos in {}",
+                duplicates.to_string().bright_yellow(),
+                path.to_string_lossy().bright_cyan()
+            );
+        }
+
+        let found = videos.len();
+
+        let undated = videos
+            .iter()
+            .filter(|video| video.raw.upload_date.is_none())
+            .count();
+
+        if undated > 0 && settings.filters.max_videos.is_some() {
+            warn!(
+                "{} videos in {} have no upload date, the latest videos can't be determined so the maximum number of videos can't be applied",
+                undated.to_string().bright_yellow(),
+                path.to_string_lossy().bright_cyan()
+            );
         }
+
+        if undated > 0 && settings.retention.max_files.is_some() {
+            warn!(
+                "{} videos in {} have no upload date, videos beyond the retention policy's maximum number of files will only be removed after being downloaded",
+                undated.to_string().bright_yellow(),
+                path.to_string_lossy().bright_cyan()
+            );
+        }
+
+        // Flat playlists usually don't provide the videos' upload date (e.g. on Youtube)
+        if found > 0 && undated == found {
+            if settings.filters.uses_upload_date() {
+                warn!(
+                    "None of the videos in {} have an upload date, upload date filters can't be applied",
+                    path.to_string_lossy().bright_cyan()
+                );
+            }
+
+            if settings.retention.max_age.is_some() {
+                warn!(
+                    "None of the videos in {} have an upload date, videos older than the retention policy's maximum age will only be removed after being downloaded",
+                    path.to_string_lossy().bright_cyan()
+                );
+            }
+        }
+
+        let videos = settings
+            .retention
+            .as_filters()
+            .apply(settings.filters.apply(videos));
+
+        if videos.len() < found {
+            info!(
+                "Filtered out {} of {} videos in {}",
+                (found - videos.len()).to_string().bright_yellow(),
+                found.to_string().bright_yellow(),
+                path.to_string_lossy().bright_cyan()
+            );
+        }
+
+        entries.extend(videos);
     }
 
-    Ok(entries)
+    Ok(FetchedPlaylists {
+        videos: entries,
+        infos,
+        with_empty_playlist,
+    })
 }
 
-fn build_approximate_indexes(
-    dirs: &HashSet<PathBuf>,
-) -> Result<HashMap<&PathBuf, HashSet<String>>> {
+fn build_approximate_indexes<'a>(
+    dirs: &'a HashSet<PathBuf>,
+    config: &Config,
+) -> Result<HashMap<&'a PathBuf, HashSet<String>>> {
     info!("Building directory index...");
 
     let dirs_ids = dirs
         .into_par_iter()
-        .map(|dir| build_approximate_index(dir).map(|ids| (dir, ids)))
+        .map(|dir| build_approximate_index(dir, config).map(|ids| (dir, ids)))
         .collect::<Result<HashMap<_, _>, _>>()?;
 
     info!("{}", "Index is ready.".bright_black());
@@ -305,7 +547,14 @@ fn build_approximate_indexes(
     Ok(dirs_ids)
 }
 
-pub fn build_approximate_index(dir: &Path) -> Result<HashSet<String>> {
+/// Build the list of IDs of videos inside a directory
+///
+/// Synchronization directories use a persistent index, other directories are scanned entirely.
+pub fn build_approximate_index(dir: &Path, config: &Config) -> Result<HashSet<String>> {
+    if dir.join(&config.url_filename).is_file() {
+        return VideoIndex::load_and_update(dir, config);
+    }
+
     let mut ids = HashSet::new();
 
     for item in WalkDir::new(dir) {
@@ -327,24 +576,118 @@ pub fn build_approximate_index(dir: &Path) -> Result<HashSet<String>> {
             }
         };
 
-        if let Some(m) = VIDEO_ID_REGEX.captures(filename) {
-            let id = m.name(ID_REGEX_MATCHING_GROUP_NAME).unwrap().as_str();
+        // Same as for synchronization directories' index
+        if filename.starts_with('.') {
+            continue;
+        }
 
-            if !id.contains('-') {
-                ids.insert(id.to_string());
-                continue;
-            }
+        ids.extend(ids_from_filename(filename));
+    }
 
-            let mut res = vec![];
+    Ok(ids)
+}
 
-            for segment in id.split('-').rev() {
-                res.push(segment);
-                ids.insert(res.iter().rev().cloned().collect::<Vec<_>>().join("-"));
-            }
+/// Extract the possible IDs of a video from its file name
+pub fn ids_from_filename(filename: &str) -> Vec<String> {
+    let Some(m) = VIDEO_ID_REGEX.captures(filename) else {
+        return vec![];
+    };
+
+    let id = m.name(ID_REGEX_MATCHING_GROUP_NAME).unwrap().as_str();
+
+    if !id.contains('-') {
+        return vec![id.to_string()];
+    }
+
+    let mut ids = vec![];
+    let mut res = vec![];
+
+    for segment in id.split('-').rev() {
+        res.push(segment);
+        ids.push(res.iter().rev().cloned().collect::<Vec<_>>().join("-"));
+    }
+
+    ids
+}
+
+/// Video file of a synchronization directory, along with the files sharing its name
+pub struct LocalVideo {
+    pub filename: String,
+
+    /// File name without its extension, shared by the sidecar files
+    pub stem: String,
+
+    /// Possible IDs of the video (see [`ids_from_filename`])
+    pub ids: Vec<String>,
+
+    /// Files sharing the video's name (e.g. subtitles, thumbnails), excluding the video file itself
+    pub sidecars: Vec<String>,
+}
+
+impl LocalVideo {
+    /// Complete ID found in the file name
+    pub fn id(&self) -> &str {
+        self.ids.last().unwrap()
+    }
+
+    /// Video file followed by its sidecar files
+    pub fn files(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.filename).chain(&self.sidecars)
+    }
+}
+
+/// List the video files of a synchronization directory (not including subdirectories), sorted by name
+///
+/// Hidden files are ignored, which includes the files still being moved to the directory.
+pub fn list_videos_with_sidecars(dir: &Path) -> Result<Vec<LocalVideo>> {
+    let mut filenames = vec![];
+
+    for entry in fs::read_dir(dir).with_context(|| {
+        format!(
+            "Failed to read synchronization directory: {}",
+            dir.to_string_lossy().bright_magenta()
+        )
+    })? {
+        let entry = entry.context("Failed to read entry of synchronization directory")?;
+
+        if entry.path().is_file()
+            && let Ok(filename) = entry.file_name().into_string()
+            && !filename.starts_with('.')
+        {
+            filenames.push(filename);
         }
     }
 
-    Ok(ids)
+    filenames.sort();
+
+    let mut videos = vec![];
+
+    for filename in &filenames {
+        let ids = ids_from_filename(filename);
+
+        if ids.is_empty() {
+            continue;
+        }
+
+        let Some((stem, _)) = filename.rsplit_once('.') else {
+            continue;
+        };
+
+        let sidecars = filenames
+            .iter()
+            .filter(|name| *name != filename && name.starts_with(&format!("{stem}.")))
+            .cloned()
+            .collect();
+
+        videos.push(LocalVideo {
+            filename: filename.clone(),
+            stem: stem.to_owned(),
+            ids,
+            sidecars,
+        });
+    }
+
+    Ok(videos)
 }
 
 fn check_videos_availability(
@@ -394,19 +737,44 @@ fn check_videos_availability(
             format!("({})", video.id).bright_black()
         );
 
-        if check_availability(&config.yt_dlp_bin, &video.raw.url)? {
-            success!("OK");
+        let failure = check_availability(&config.yt_dlp_bin, &video.raw.url)?;
+
+        // YT-DLP fails when interrupted, which must not be mistaken for the video being unavailable
+        if is_interrupted() {
+            bail!("Availability checking was interrupted");
+        }
+
+        match failure {
+            None => {
+                success!("OK");
+
+                available.push(video);
+            }
 
-            available.push(video);
-        } else {
-            error!("ERROR");
+            Some(failure) if failure.is_permanent() => {
+                error!("ERROR ({failure})");
+
+                blacklist_video(
+                    &sync_dir
+                        .join(&video.sync_dir)
+                        .join(&config.auto_blacklist_filename),
+                    &BlacklistEntry::with_details(
+                        video.raw.ie_key.clone(),
+                        video.id.clone(),
+                        BlacklistEntryDetails {
+                            reason: Some(BlacklistReason::Unavailable(failure)),
+                            added_at: Some(now_timestamp()),
+                            title: Some(video.raw.title.clone()),
+                            comment: None,
+                        },
+                    ),
+                )?;
+            }
 
-            blacklist_video(
-                &sync_dir
-                    .join(&video.sync_dir)
-                    .join(&config.auto_blacklist_filename),
-                &BlacklistEntry::new(video.raw.ie_key.clone(), video.id.clone()),
-            )?;
+            // Temporary failures will be checked again on the next synchronization
+            Some(failure) => {
+                warn!("ERROR ({failure}), skipping it for now");
+            }
         }
     }
 
@@ -415,24 +783,43 @@ fn check_videos_availability(
     Ok(available)
 }
 
-fn remove_downloaded_entries(from: Cache) -> Result<Cache> {
+fn remove_downloaded_entries(from: Cache, config: &Config) -> Result<Cache> {
     let sync_dirs = from
         .entries
         .iter()
         .map(|entry| entry.sync_dir.clone())
         .collect::<HashSet<_>>();
 
-    let indexes = build_approximate_indexes(&sync_dirs)?;
+    let indexes = build_approximate_indexes(&sync_dirs, config)?;
 
     Ok(Cache::new(
         from.entries
             .into_iter()
             .filter(|video| !indexes.get(&video.sync_dir).expect("Internal consistency error: failed to get index for given video's sync. directory").contains(&video.id))
             .collect::<Vec<_>>(),
+        from.playlists,
+        from.playlists_order,
+        from.show_metadata,
     ))
 }
 
-struct PlaylistUrl {
-    sync_dir: PathBuf,
-    url: String,
+pub struct FetchedPlaylists {
+    /// Videos of all playlists
+    pub videos: Vec<PlatformVideo>,
+
+    /// Metadata of each directory's first playlist
+    pub infos: HashMap<PathBuf, RawPlaylistInfos>,
+
+    /// Directories for which at least one playlist returned no video at all
+    pub with_empty_playlist: HashSet<PathBuf>,
+}
+
+pub struct PlaylistUrl {
+    /// Synchronization directory, relative to the root synchronization directory
+    pub sync_dir: PathBuf,
+
+    /// URLs of the playlists to synchronize into the directory
+    pub urls: Vec<String>,
+
+    pub settings: SyncSettings,
 }
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     fs,
     path::{Path, PathBuf},
 };
@@ -6,22 +7,47 @@ use std::{
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
-use crate::utils::ytdlp::RawVideoInfos;
+use crate::{dl::PositionInPlaylist, utils::ytdlp::RawVideoInfos};
+
+use super::{m3u::PlaylistTrack, show_metadata::ShowMetadataTarget};
 
 #[derive(Serialize, Deserialize)]
 pub struct Cache {
     pub entries: Vec<CacheEntry>,
     pub max_index: usize,
+
+    /// Synchronization directories the cache was built from
+    #[serde(default)]
+    pub playlists: Vec<PathBuf>,
+
+    /// Order of the videos in each directory's playlist(s), remembered once the synchronization starts
+    #[serde(default)]
+    pub playlists_order: HashMap<PathBuf, Vec<PlaylistTrack>>,
+
+    /// Directories whose media server metadata must be written once the synchronization completes
+    #[serde(default)]
+    pub show_metadata: HashMap<PathBuf, ShowMetadataTarget>,
 }
 
 impl Cache {
-    pub fn new(entries: Vec<CacheEntry>) -> Self {
+    pub fn new(
+        entries: Vec<CacheEntry>,
+        playlists: Vec<PathBuf>,
+        playlists_order: HashMap<PathBuf, Vec<PlaylistTrack>>,
+        show_metadata: HashMap<PathBuf, ShowMetadataTarget>,
+    ) -> Self {
         let max_index = match entries.iter().map(|entry| entry.index).max() {
             Some(index) => index + 1,
             None => 0,
         };
 
-        Self { entries, max_index }
+        Self {
+            entries,
+            max_index,
+            playlists,
+            playlists_order,
+            show_metadata,
+        }
     }
 
     pub fn load_from_disk(path: &Path) -> Result<Self> {
@@ -46,6 +72,10 @@ pub struct CacheEntry {
     pub url: String,
     pub index: usize,
     pub sync_dir: PathBuf,
+
+    /// Position of the video in its source playlist(s)
+    #[serde(default)]
+    pub position: Option<PositionInPlaylist>,
 }
 
 impl CacheEntry {
@@ -54,11 +84,20 @@ impl CacheEntry {
             raw,
             sync_dir,
             id,
+            position,
             needs_checking: _,
         } = video;
 
         #[forbid(unused_variables)]
-        let RawVideoInfos { ie_key, title, url } = raw;
+        let RawVideoInfos {
+            ie_key,
+            title,
+            url,
+            duration: _,
+            upload_date: _,
+            uploader: _,
+            channel: _,
+        } = raw;
 
         Self {
             ie_key,
@@ -67,6 +106,7 @@ impl CacheEntry {
             url,
             index,
             sync_dir,
+            position: Some(position),
         }
     }
 }
@@ -81,5 +121,6 @@ pub struct PlatformVideo {
     pub raw: RawVideoInfos,
     pub sync_dir: PathBuf,
     pub id: String,
+    pub position: PositionInPlaylist,
     pub needs_checking: bool,
 }
@@ -1,5 +1,9 @@
+use std::time::Duration;
+
 use clap::{Args, Subcommand};
 
+use crate::utils::time::parse_duration;
+
 #[derive(Args)]
 pub struct SyncArgs {
     #[clap(subcommand)]
@@ -16,13 +20,134 @@ pub enum SyncAction {
     Run {
         #[clap(long = "dry-run", help = "Simulate the synchronization")]
         dry_run: bool,
+
+        #[clap(
+            short = 'j',
+            long,
+            help = "Number of videos to download at the same time"
+        )]
+        parallel: Option<usize>,
+
+        #[clap(short, long, help = "Don't ask for confirmation before downloading")]
+        yes: bool,
+
+        #[clap(
+            long,
+            help = "Wait for other synchronizations of the same directory to finish"
+        )]
+        wait: bool,
     },
 
-    Blacklist {
+    #[clap(about = "Show an overview of all the playlists of the synchronization tree")]
+    Status {
+        #[clap(long, help = "Output the report as JSON")]
+        json: bool,
+    },
+
+    Blacklist(BlacklistArgs),
+
+    #[clap(about = "List, move or delete the files of videos removed from their playlist")]
+    Mirror {
+        #[clap(
+            long = "move",
+            help = "Move the files to a '_removed' directory",
+            conflicts_with = "delete"
+        )]
+        move_files: bool,
+
+        #[clap(long, help = "Delete the files (asks for confirmation)")]
+        delete: bool,
+
+        #[clap(
+            short,
+            long,
+            help = "Don't ask for confirmation before deleting, and allow removing most of a directory's videos"
+        )]
+        yes: bool,
+    },
+
+    #[clap(about = "Rename files to match the videos' current position in their playlist")]
+    Renumber {
+        #[clap(long = "dry-run", help = "Only list the files that would be renamed")]
+        dry_run: bool,
+    },
+
+    #[clap(about = "Generate the M3U8 playlist of each directory from the last fetched playlists")]
+    ExportM3u,
+
+    #[clap(about = "Download the missing subtitles of already downloaded videos")]
+    Subtitles {
+        #[clap(long = "dry-run", help = "Only list the videos without subtitles")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Args)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+pub struct BlacklistArgs {
+    #[clap(subcommand)]
+    pub action: Option<BlacklistAction>,
+
+    /// Shorthand for the `add` subcommand
+    #[clap(flatten)]
+    pub add: BlacklistAddArgs,
+}
+
+#[derive(Args)]
+pub struct BlacklistAddArgs {
+    #[clap(required = true, help = "Platform the video belongs to")]
+    pub platform: Option<String>,
+
+    #[clap(required = true, help = "ID of the video to blacklist")]
+    pub video_id: Option<String>,
+
+    #[clap(long, help = "Comment explaining why the video is blacklisted")]
+    pub comment: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum BlacklistAction {
+    #[clap(about = "Blacklist a video in the current directory")]
+    Add(BlacklistAddArgs),
+
+    #[clap(about = "List the blacklisted videos of all synchronization directories")]
+    List {
+        #[clap(
+            long,
+            help = "Only list automatically blacklisted videos",
+            conflicts_with = "custom"
+        )]
+        auto: bool,
+
+        #[clap(long, help = "Only list manually blacklisted videos")]
+        custom: bool,
+    },
+
+    #[clap(about = "Search blacklisted videos by ID, title or comment")]
+    Search {
+        #[clap(help = "Text to search (case-insensitive)")]
+        query: String,
+    },
+
+    #[clap(about = "Remove a video from all blacklists")]
+    Remove {
         #[clap(help = "Platform the video belongs to")]
         platform: String,
 
-        #[clap(help = "ID of the video to blacklist")]
+        #[clap(help = "ID of the video to remove")]
         video_id: String,
     },
+
+    #[clap(about = "Remove expired entries from the automatic blacklists")]
+    Prune {
+        #[clap(
+            long,
+            help = "Remove entries older than the provided duration (e.g. '30d') instead of the configured expiry",
+            value_parser = parse_duration
+        )]
+        older_than: Option<Duration>,
+
+        #[clap(long = "dry-run", help = "Only list the entries that would be removed")]
+        dry_run: bool,
+    },
 }
@@ -1,8 +1,18 @@
-use super::cache::Cache;
-use crate::info;
+use std::{collections::HashMap, path::PathBuf};
+
+use super::{cache::Cache, settings::SyncSettings};
+use crate::{
+    config::{Config, PlatformDownloadOptions, UseCookiesFrom},
+    dl::{QualitySpec, SingleDlArgs, DEFAULT_FILENAMING, DEFAULT_GOOD_VIDEO_QUALITY},
+    info,
+};
 use colored::Colorize;
 
-pub fn display_sync(cache: &Cache) {
+pub fn display_sync(
+    cache: &Cache,
+    dirs_settings: &HashMap<PathBuf, SyncSettings>,
+    config: &Config,
+) {
     let max_index = cache
         .entries
         .iter()
@@ -30,4 +40,132 @@ pub fn display_sync(cache: &Cache) {
             entry.title.bright_yellow()
         );
     }
+
+    let mut dirs_platforms = vec![];
+
+    for entry in &cache.entries {
+        if !dirs_platforms.contains(&(&entry.sync_dir, &entry.ie_key)) {
+            dirs_platforms.push((&entry.sync_dir, &entry.ie_key));
+        }
+    }
+
+    if dirs_platforms.is_empty() {
+        return;
+    }
+
+    info!("");
+    info!("Download options:");
+
+    for (dir, ie_key) in dirs_platforms {
+        let (Some(settings), Some(platform)) =
+            (dirs_settings.get(dir), config.platforms.get(ie_key))
+        else {
+            continue;
+        };
+
+        let args = settings.dl_options.apply(SingleDlArgs::default());
+
+        info!(
+            "  {} ({}): {}",
+            dir.to_string_lossy().bright_cyan(),
+            ie_key.bright_blue(),
+            describe_dl_options(&args, &platform.dl_options, config).join(", ")
+        );
+    }
+}
+
+/// Describe the options a video will be downloaded with, after resolving the platform's and the global ones
+fn describe_dl_options(
+    args: &SingleDlArgs,
+    platform: &PlatformDownloadOptions,
+    config: &Config,
+) -> Vec<String> {
+    let quality = match (&args.raw_format, &args.custom_quality, args.quality) {
+        (Some(raw_format), _, _) => format!("raw format {raw_format}"),
+        (None, Some(custom_quality), _) => custom_quality.to_string(),
+        (None, None, Some(quality)) => format!("{quality:?}"),
+        (None, None, None) => match &platform.default_quality {
+            Some(QualitySpec::Preset(quality)) => format!("{quality:?}"),
+            Some(QualitySpec::Custom(quality)) => quality.to_string(),
+            None => format!("{DEFAULT_GOOD_VIDEO_QUALITY:?}"),
+        },
+    };
+
+    let mut options = vec![format!("quality {}", quality.bright_yellow())];
+
+    let filenaming = args.filenaming.as_deref().unwrap_or(DEFAULT_FILENAMING);
+
+    if filenaming != DEFAULT_FILENAMING {
+        options.push(format!("filenaming {}", filenaming.bright_yellow()));
+    }
+
+    if args.index_prefix == Some(true) {
+        options.push("index prefix".to_owned());
+    }
+
+    if let Some(format) = args
+        .output_format
+        .as_ref()
+        .or(platform.output_format.as_ref())
+    {
+        options.push(format!("output format {}", format.bright_yellow()));
+    }
+
+    if let Some(bandwidth_limit) = args
+        .limit_bandwidth
+        .as_ref()
+        .or(platform.bandwidth_limit.as_ref())
+        .or(config.default_bandwidth_limit.as_ref())
+    {
+        options.push(format!("limited to {}", bandwidth_limit.bright_yellow()));
+    }
+
+    match args.cookies.as_ref().or(platform.cookies.as_ref()) {
+        Some(UseCookiesFrom::Browser(browser)) => {
+            options.push(format!("cookies from {}", browser.bright_yellow()))
+        }
+        Some(UseCookiesFrom::File(file)) => {
+            options.push(format!("cookies from {}", file.bright_yellow()))
+        }
+        None => {}
+    }
+
+    if args.no_thumbnail.or(platform.no_thumbnail) == Some(true) {
+        options.push("no thumbnail".to_owned());
+    }
+
+    if args.skip_repair_date.or(platform.skip_repair_date) == Some(true) {
+        options.push("no date repair".to_owned());
+    }
+
+    if args.rate_limited.or(platform.rate_limited) == Some(true) {
+        options.push("rate limited".to_owned());
+    }
+
+    if let Some(subtitles) = args.subtitles().or_else(|| platform.subtitles.clone()) {
+        options.push(format!(
+            "subtitles {}",
+            subtitles.to_string().bright_yellow()
+        ));
+    }
+
+    if let Some(sponsorblock) = &platform.sponsorblock {
+        options.push(format!(
+            "SponsorBlock {}",
+            sponsorblock.to_string().bright_yellow()
+        ));
+    }
+
+    if args.write_nfo.or(platform.write_nfo) == Some(true) {
+        options.push("NFO files".to_owned());
+    }
+
+    if !args.forward_ytdlp_args.is_empty() {
+        options.push(format!(
+            "extra arguments {}",
+            args.forward_ytdlp_args.join(" ").bright_yellow()
+        ));
+    }
+
+    options
 }
@@ -0,0 +1,169 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, warn};
+
+use super::builder::ids_from_filename;
+
+/// Persistent index of the videos downloaded inside a synchronization directory
+///
+/// Each directory of the tree is indexed separately along with its modification time,
+/// so only the directories whose content changed since the last run need to be read again.
+#[derive(Serialize, Deserialize, Default)]
+pub struct VideoIndex {
+    dirs: HashMap<PathBuf, IndexedDir>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct IndexedDir {
+    /// Modification time of the directory when it was indexed
+    /// (`None` if it was too recent to be trusted)
+    mtime: Option<SystemTime>,
+
+    /// Name of the subdirectories
+    subdirs: Vec<String>,
+
+    /// IDs extracted from the files' name
+    ids: Vec<String>,
+}
+
+impl VideoIndex {
+    /// Load the index of a synchronization directory and update it to reflect its current content
+    pub fn load_and_update(dir: &Path, config: &Config) -> Result<HashSet<String>> {
+        let index_path = dir.join(&config.index_filename);
+
+        // Parallel downloads may update the same index, so the whole update must be done at once
+        // to not lose changes made by another worker in-between
+        let _guard = INDEX_FILES_LOCK.lock().unwrap();
+
+        let old_index = Self::load_from_disk(&index_path);
+
+        let mut index = Self::default();
+        index.update_dir(dir, Path::new(""), &old_index)?;
+
+        let ids = index
+            .dirs
+            .values()
+            .flat_map(|dir| dir.ids.iter().cloned())
+            .collect();
+
+        index.save_to_disk(&index_path)?;
+
+        Ok(ids)
+    }
+
+    fn load_from_disk(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+
+        let index = fs::read_to_string(path)
+            .context("Failed to read index file")
+            .and_then(|index| serde_json::from_str(&index).context("Failed to decode index file"));
+
+        index.unwrap_or_else(|err| {
+            warn!(
+                "Failed to load index at path {}, rebuilding it: {err}",
+                path.to_string_lossy().bright_magenta()
+            );
+
+            Self::default()
+        })
+    }
+
+    fn save_to_disk(&self, path: &Path) -> Result<()> {
+        // Writing in place doesn't change the parent directory's modification time,
+        // which would otherwise invalidate it on every run
+        fs::write(
+            path,
+            serde_json::to_string(self).context("Failed to serialize index content")?,
+        )
+        .context("Failed to write index file")
+    }
+
+    fn update_dir(&mut self, root: &Path, relative: &Path, old_index: &Self) -> Result<()> {
+        let path = root.join(relative);
+
+        let mtime = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| {
+                format!(
+                    "Failed to get modification time of directory: {}",
+                    path.to_string_lossy().bright_magenta()
+                )
+            })?;
+
+        let indexed = match old_index.dirs.get(relative) {
+            Some(indexed) if indexed.mtime == Some(mtime) => indexed.clone(),
+            _ => index_dir(&path, mtime)?,
+        };
+
+        for subdir in &indexed.subdirs {
+            self.update_dir(root, &relative.join(subdir), old_index)?;
+        }
+
+        self.dirs.insert(relative.to_path_buf(), indexed);
+
+        Ok(())
+    }
+}
+
+fn index_dir(path: &Path, mtime: SystemTime) -> Result<IndexedDir> {
+    let mut subdirs = vec![];
+    let mut ids = vec![];
+
+    let entries = fs::read_dir(path).with_context(|| {
+        format!(
+            "Failed to read directory while building index: {}",
+            path.to_string_lossy().bright_magenta()
+        )
+    })?;
+
+    for entry in entries {
+        let entry = entry.context("Failed to read directory entry while building index")?;
+
+        let Some(filename) = entry.file_name().to_str().map(str::to_owned) else {
+            warn!(
+                "Ignoring item with non-UTF-8 name: {}",
+                entry.file_name().to_string_lossy()
+            );
+            continue;
+        };
+
+        let file_type = entry
+            .file_type()
+            .context("Failed to get directory entry's type while building index")?;
+
+        // Hidden files aren't videos (e.g. the SponsorBlock record) or are still being moved to the directory
+        if file_type.is_dir() {
+            subdirs.push(filename);
+        } else if entry.path().is_file() && !filename.starts_with('.') {
+            ids.extend(ids_from_filename(&filename));
+        }
+    }
+
+    // Changes made right after the directory was modified may share the same modification time,
+    // so recently-modified directories will be read again next time
+    let is_old_enough = SystemTime::now()
+        .duration_since(mtime)
+        .is_ok_and(|elapsed| elapsed > RACY_MTIME_DELAY);
+
+    Ok(IndexedDir {
+        mtime: Some(mtime).filter(|_| is_old_enough),
+        subdirs,
+        ids,
+    })
+}
+
+static INDEX_FILES_LOCK: Mutex<()> = Mutex::new(());
+
+static RACY_MTIME_DELAY: Duration = Duration::from_secs(2);
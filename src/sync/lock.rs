@@ -0,0 +1,330 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions, TryLockError},
+    io::{ErrorKind, Read, Seek, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use walkdir::WalkDir;
+
+use crate::{
+    config::Config,
+    info,
+    utils::time::{format_timestamp, now_timestamp},
+    warn,
+};
+
+/// Advisory lock preventing concurrent synchronizations of the same tree
+///
+/// The lock file is exclusively locked (using the OS' file locks) for as long as this value lives,
+/// so a lock file that isn't locked was left by a process which didn't exit cleanly.
+/// The lock file is removed when this value is dropped.
+pub struct SyncLock {
+    path: PathBuf,
+
+    /// Opened lock file, holding the OS lock
+    _file: File,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LockOwner {
+    pid: u32,
+    hostname: String,
+    since: u64,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            hostname: hostname(),
+            since: now_timestamp(),
+        }
+    }
+}
+
+enum LockStatus {
+    /// No lock file
+    Free,
+
+    /// Lock file of a running synchronization, along with its description
+    Held(String),
+
+    /// Lock file left by a process which didn't exit cleanly, locked by the current process
+    ///
+    /// The owner is unknown if the process exited before writing it.
+    Stale {
+        file: File,
+        owner: Option<LockOwner>,
+        content: String,
+    },
+}
+
+impl SyncLock {
+    /// Lock a synchronization directory, optionally waiting for it to be released by its current owner
+    pub fn acquire(sync_dir: &Path, wait: bool, config: &Config) -> Result<Self> {
+        let sync_dir = fs::canonicalize(sync_dir)
+            .context("Failed to canonicalize synchronization directory")?;
+
+        let mut waiting = false;
+
+        loop {
+            let owner = match Self::try_acquire(&sync_dir, config)? {
+                // Synchronizations running in a subdirectory overlap with this one too
+                Ok(lock) => match find_held_nested_lock(&sync_dir, config)? {
+                    None => return Ok(lock),
+                    Some(owner) => owner,
+                },
+
+                Err(owner) => owner,
+            };
+
+            if !wait {
+                bail!(
+                    "{owner}\nUse {} to wait for it to finish. If no synchronization is running, remove the lock file.",
+                    "--wait".bright_cyan()
+                );
+            }
+
+            if !waiting {
+                info!("{}, waiting for it to be released...", owner);
+                waiting = true;
+            }
+
+            std::thread::sleep(LOCK_POLLING_INTERVAL);
+        }
+    }
+
+    fn try_acquire(sync_dir: &Path, config: &Config) -> Result<Result<Self, String>> {
+        // Synchronizations running in a parent directory overlap with this one
+        for dir in sync_dir.ancestors().skip(1) {
+            let path = dir.join(&config.lock_filename);
+
+            if let LockStatus::Held(owner) = check_lock(&path)? {
+                return Ok(Err(owner));
+            }
+        }
+
+        let path = sync_dir.join(&config.lock_filename);
+
+        loop {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path);
+
+            match file {
+                Ok(file) => {
+                    match file.try_lock() {
+                        Ok(()) => {}
+
+                        // Another process took the lock file over in-between
+                        Err(TryLockError::WouldBlock) => {
+                            return Ok(Err(describe_unknown_owner(&path)));
+                        }
+
+                        Err(TryLockError::Error(err)) => {
+                            return Err(err).context("Failed to lock the lock file");
+                        }
+                    }
+
+                    write_owner(&file, &LockOwner::current())?;
+
+                    return Ok(Ok(Self { path, _file: file }));
+                }
+
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => match check_lock(&path)? {
+                    // Lock file was removed in-between, try again
+                    LockStatus::Free => continue,
+
+                    LockStatus::Held(owner) => return Ok(Err(owner)),
+
+                    LockStatus::Stale {
+                        file,
+                        owner,
+                        content,
+                    } => {
+                        // Ensure the lock file wasn't replaced between its opening and its locking
+                        if fs::read_to_string(&path).ok().as_ref() != Some(&content) {
+                            continue;
+                        }
+
+                        match owner {
+                            Some(owner) => warn!(
+                                "Taking over stale lock file left by process {} (started on {})",
+                                owner.pid.to_string().bright_yellow(),
+                                format_timestamp(owner.since).bright_yellow()
+                            ),
+                            None => warn!("Taking over empty stale lock file"),
+                        }
+
+                        // The lock file is rewritten in place instead of being removed,
+                        // so other processes can't create a new one meanwhile
+                        write_owner(&file, &LockOwner::current())?;
+
+                        return Ok(Ok(Self { path, _file: file }));
+                    }
+                },
+
+                Err(err) => {
+                    return Err(err).with_context(|| {
+                        format!(
+                            "Failed to create lock file at path: {}",
+                            path.to_string_lossy().bright_magenta()
+                        )
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SyncLock {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_file(&self.path) {
+            warn!(
+                "Failed to remove lock file at path {}: {err}",
+                self.path.to_string_lossy().bright_magenta()
+            );
+        }
+    }
+}
+
+/// Check if a lock file is held by a running synchronization
+///
+/// Stale lock files are locked by the current process until the returned value is dropped.
+fn check_lock(path: &Path) -> Result<LockStatus> {
+    let mut file = match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(LockStatus::Free),
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!(
+                    "Failed to open lock file at path: {}",
+                    path.to_string_lossy().bright_magenta()
+                )
+            });
+        }
+    };
+
+    let mut content = String::new();
+
+    file.read_to_string(&mut content).with_context(|| {
+        format!(
+            "Failed to read lock file at path: {}",
+            path.to_string_lossy().bright_magenta()
+        )
+    })?;
+
+    // An empty lock file was either just created and its owner is still being written,
+    // or left by a process which didn't exit before writing it
+    let owner = if content.trim().is_empty() {
+        None
+    } else {
+        match serde_json::from_str::<LockOwner>(&content) {
+            Ok(owner) => Some(owner),
+            Err(_) => bail!(
+                "Lock file at path {} is invalid, please remove it manually if no synchronization is running",
+                path.to_string_lossy().bright_magenta()
+            ),
+        }
+    };
+
+    // Locks held by processes running on other machines (e.g. for network shares) can't be checked
+    if let Some(owner) = &owner
+        && owner.hostname != hostname()
+    {
+        return Ok(LockStatus::Held(describe_owner(path, owner)));
+    }
+
+    match file.try_lock() {
+        Ok(()) => Ok(LockStatus::Stale {
+            file,
+            owner,
+            content,
+        }),
+        Err(TryLockError::WouldBlock) => Ok(LockStatus::Held(match &owner {
+            Some(owner) => describe_owner(path, owner),
+            None => describe_unknown_owner(path),
+        })),
+        Err(TryLockError::Error(err)) => Err(err).with_context(|| {
+            format!(
+                "Failed to lock the lock file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        }),
+    }
+}
+
+/// Find a lock file held by a synchronization running in a subdirectory, returning its description
+fn find_held_nested_lock(sync_dir: &Path, config: &Config) -> Result<Option<String>> {
+    for item in WalkDir::new(sync_dir).min_depth(2) {
+        let item = item.context("Failed to read directory entry while looking for lock files")?;
+
+        if item.file_type().is_file()
+            && item.file_name() == config.lock_filename.as_str()
+            && let LockStatus::Held(owner) = check_lock(item.path())?
+        {
+            return Ok(Some(owner));
+        }
+    }
+
+    Ok(None)
+}
+
+fn write_owner(mut file: &File, owner: &LockOwner) -> Result<()> {
+    file.set_len(0).context("Failed to truncate lock file")?;
+    file.rewind().context("Failed to rewind lock file")?;
+
+    file.write_all(
+        serde_json::to_string_pretty(owner)
+            .context("Failed to serialize lock file content")?
+            .as_bytes(),
+    )
+    .context("Failed to write lock file")
+}
+
+fn describe_unknown_owner(path: &Path) -> String {
+    format!(
+        "Directory is already being synchronized by another process (lock file: {})",
+        path.to_string_lossy().bright_magenta()
+    )
+}
+
+fn describe_owner(path: &Path, owner: &LockOwner) -> String {
+    let LockOwner {
+        pid,
+        hostname,
+        since,
+    } = owner;
+
+    format!(
+        "Directory is already being synchronized by process {} on host {} since {} (lock file: {})",
+        pid.to_string().bright_yellow(),
+        hostname.bright_cyan(),
+        format_timestamp(*since).bright_yellow(),
+        path.to_string_lossy().bright_magenta()
+    )
+}
+
+fn hostname() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| {
+            Command::new("hostname")
+                .output()
+                .ok()
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+        })
+        .map(|hostname| hostname.trim().to_owned())
+        .unwrap_or_default()
+}
+
+static LOCK_POLLING_INTERVAL: Duration = Duration::from_secs(5);
@@ -0,0 +1,239 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Config, info, success, warn};
+
+use super::{
+    builder::{find_playlists, ids_from_filename, list_videos_with_sidecars},
+    cache::PlatformVideo,
+    lock::SyncLock,
+    state::SyncState,
+};
+
+/// Video of a remote playlist, as remembered to generate the M3U8 playlists
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlaylistTrack {
+    pub id: String,
+    pub title: String,
+
+    /// Duration in seconds
+    pub duration: Option<f64>,
+}
+
+/// Determine the order of the videos in each synchronization directory's playlist(s)
+pub fn playlists_order(
+    dirs: &HashSet<PathBuf>,
+    videos: &[PlatformVideo],
+) -> HashMap<PathBuf, Vec<PlaylistTrack>> {
+    let mut tracks = dirs
+        .iter()
+        .map(|dir| (dir, vec![]))
+        .collect::<HashMap<_, _>>();
+
+    for video in videos {
+        tracks
+            .get_mut(&video.sync_dir)
+            .expect("Internal consistency error: sync. directory not found for given video")
+            .push((video.position.index, video));
+    }
+
+    tracks
+        .into_iter()
+        .map(|(dir, mut videos)| {
+            videos.sort_by_key(|(index, _)| *index);
+
+            let videos = videos
+                .into_iter()
+                .map(|(_, video)| PlaylistTrack {
+                    id: video.id.clone(),
+                    title: video.raw.title.clone(),
+                    duration: video.raw.duration,
+                })
+                .collect();
+
+            (dir.clone(), videos)
+        })
+        .collect()
+}
+
+/// Remember the order of the videos in each synchronization directory's playlist(s)
+pub fn save_playlists_order(
+    order: &HashMap<PathBuf, Vec<PlaylistTrack>>,
+    config: &Config,
+    sync_dir: &Path,
+) -> Result<()> {
+    for (dir, tracks) in order {
+        let dir = sync_dir.join(dir);
+
+        let mut state = SyncState::load(&dir, config)?;
+        state.playlist = tracks.clone();
+        state.save(&dir, config)?;
+    }
+
+    Ok(())
+}
+
+/// Generate the M3U8 playlist of the provided synchronization directories
+pub fn write_m3u_playlists(playlists: &[PathBuf], config: &Config, sync_dir: &Path) -> Result<()> {
+    for playlist in playlists {
+        write_m3u_playlist(&sync_dir.join(playlist), config)?;
+    }
+
+    Ok(())
+}
+
+/// Generate the M3U8 playlist of a synchronization directory, listing its files in the remote playlist's order
+///
+/// Returns the number of listed files, or `None` if the playlist's order isn't known yet (or if M3U8 playlists
+/// are disabled).
+pub fn write_m3u_playlist(dir: &Path, config: &Config) -> Result<Option<usize>> {
+    match M3uPlaylist::load(dir, config)? {
+        Some(playlist) => playlist.write().map(Some),
+        None => Ok(None),
+    }
+}
+
+/// M3U8 playlist of a synchronization directory, which can be kept up-to-date as videos are downloaded
+/// without scanning the directory again
+pub struct M3uPlaylist {
+    path: PathBuf,
+    dir: PathBuf,
+    tracks: Vec<PlaylistTrack>,
+
+    /// Name of the file of each video ID found in the directory
+    files_by_id: HashMap<String, String>,
+}
+
+impl M3uPlaylist {
+    /// Load the playlist's order and scan the directory's files
+    ///
+    /// Returns `None` if the playlist's order isn't known yet (or if M3U8 playlists are disabled).
+    pub fn load(dir: &Path, config: &Config) -> Result<Option<Self>> {
+        let Some(m3u_filename) = &config.m3u_playlist_filename else {
+            return Ok(None);
+        };
+
+        let state = SyncState::load(dir, config)?;
+
+        if state.playlist.is_empty() {
+            return Ok(None);
+        }
+
+        let mut playlist = Self {
+            path: dir.join(m3u_filename),
+            dir: dir.to_owned(),
+            tracks: state.playlist,
+            files_by_id: HashMap::new(),
+        };
+
+        playlist.rescan()?;
+
+        Ok(Some(playlist))
+    }
+
+    /// Scan the directory's files again
+    pub fn rescan(&mut self) -> Result<()> {
+        let videos = list_videos_with_sidecars(&self.dir)?;
+
+        self.files_by_id.clear();
+
+        for video in videos {
+            self.add_file(video.filename);
+        }
+
+        Ok(())
+    }
+
+    /// Register a file added to the directory
+    pub fn add_file(&mut self, filename: String) {
+        for id in ids_from_filename(&filename) {
+            self.files_by_id
+                .entry(id)
+                .or_insert_with(|| filename.clone());
+        }
+    }
+
+    /// Write the playlist if its content changed, returning the number of listed files
+    pub fn write(&self) -> Result<usize> {
+        let mut content = String::from("#EXTM3U\n");
+        let mut tracks = 0;
+
+        for PlaylistTrack {
+            id,
+            title,
+            duration,
+        } in &self.tracks
+        {
+            let Some(filename) = self.files_by_id.get(id) else {
+                continue;
+            };
+
+            // Lines starting with a '#' would be treated as comments
+            let prefix = if filename.starts_with('#') { "./" } else { "" };
+
+            content.push_str(&format!(
+                "#EXTINF:{},{}\n{prefix}{filename}\n",
+                duration.map_or(-1, |duration| duration.round() as i64),
+                title.replace(['\r', '\n'], " ")
+            ));
+
+            tracks += 1;
+        }
+
+        if fs::read_to_string(&self.path).is_ok_and(|existing| existing == content) {
+            return Ok(tracks);
+        }
+
+        fs::write(&self.path, content).with_context(|| {
+            format!(
+                "Failed to write M3U8 playlist at path: {}",
+                self.path.to_string_lossy().bright_magenta()
+            )
+        })?;
+
+        Ok(tracks)
+    }
+}
+
+/// Generate the M3U8 playlist of all synchronization directories, without fetching the playlists again
+pub fn export_m3u(config: &Config, sync_dir: &Path) -> Result<()> {
+    if config.m3u_playlist_filename.is_none() {
+        bail!(
+            "M3U8 playlists are disabled, set {} in the configuration to enable them",
+            "m3u_playlist_filename".bright_cyan()
+        );
+    }
+
+    let _lock = SyncLock::acquire(sync_dir, false, config)?;
+
+    let mut playlists = find_playlists(sync_dir, config)?;
+    playlists.sort_by_key(|playlist| playlist.sync_dir.to_string_lossy().to_lowercase());
+
+    for playlist in &playlists {
+        let dir = playlist.sync_dir.to_string_lossy();
+
+        match write_m3u_playlist(&sync_dir.join(&playlist.sync_dir), config)? {
+            Some(tracks) => info!(
+                "| Listed {} files in the playlist of {}",
+                tracks.to_string().bright_yellow(),
+                dir.bright_cyan()
+            ),
+
+            None => warn!(
+                "| Order of the playlist of {} is unknown, synchronize it first",
+                dir.bright_cyan()
+            ),
+        }
+    }
+
+    success!("Done!");
+
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, IsTerminal},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use colored::Colorize;
+use inquire::Confirm;
+
+use crate::{config::Config, info, success, warn};
+
+use super::{
+    builder::{
+        FetchedPlaylists, fetch_playlists_detailed, find_playlists, list_videos_with_sidecars,
+    },
+    lock::SyncLock,
+};
+
+/// What to do with the local files of videos removed from their playlist
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum MirrorAction {
+    Report,
+    Move,
+    Delete,
+}
+
+/// Find local files whose video is no longer in the playlist, and handle them
+///
+/// Files without a recognized video ID are never touched.
+pub fn mirror(action: MirrorAction, yes: bool, config: &Config, sync_dir: &Path) -> Result<()> {
+    let _lock = SyncLock::acquire(sync_dir, false, config)?;
+
+    let playlists = find_playlists(sync_dir, config)?;
+
+    let mut remote_ids = playlists
+        .iter()
+        .map(|playlist| (playlist.sync_dir.clone(), HashSet::new()))
+        .collect::<HashMap<_, _>>();
+
+    let FetchedPlaylists {
+        videos,
+        infos: _,
+        with_empty_playlist,
+    } = fetch_playlists_detailed(playlists, false, config)?;
+
+    for video in videos {
+        remote_ids
+            .get_mut(&video.sync_dir)
+            .expect("Internal consistency error: sync. directory not found for given video")
+            .insert(video.id);
+    }
+
+    let mut dirs = remote_ids.into_iter().collect::<Vec<_>>();
+    dirs.sort_by_key(|(dir, _)| dir.to_string_lossy().to_lowercase());
+
+    let mut removed = vec![];
+
+    for (dir, ids) in dirs {
+        // Protects against removing a whole playlist's videos if the platform returned it empty
+        if with_empty_playlist.contains(&dir) {
+            warn!(
+                "Ignoring directory {} as one of its playlists appears to be empty",
+                dir.to_string_lossy().bright_cyan()
+            );
+
+            continue;
+        }
+
+        let RemovedFiles {
+            files,
+            videos,
+            total_videos,
+        } = find_removed_files(&sync_dir.join(&dir), &ids)?;
+
+        // Protects against partial results from the platform
+        if action != MirrorAction::Report
+            && !yes
+            && videos as f64 > total_videos as f64 * MAX_REMOVED_RATIO
+        {
+            warn!(
+                "Ignoring directory {} as {} of its {} videos would be removed, use {} to proceed anyway",
+                dir.to_string_lossy().bright_cyan(),
+                videos.to_string().bright_yellow(),
+                total_videos.to_string().bright_yellow(),
+                "--yes".bright_cyan()
+            );
+
+            continue;
+        }
+
+        removed.extend(files);
+    }
+
+    if removed.is_empty() {
+        success!("No video was removed from the playlists!");
+        return Ok(());
+    }
+
+    info!(
+        "Found {} files of videos removed from their playlist:",
+        removed.len().to_string().bright_yellow()
+    );
+
+    for file in &removed {
+        info!(
+            "* {}",
+            file.strip_prefix(sync_dir)
+                .unwrap_or(file)
+                .to_string_lossy()
+                .bright_magenta()
+        );
+    }
+
+    match action {
+        MirrorAction::Report => {
+            info!("");
+            info!(
+                "Use {} to move them to a {} directory, or {} to delete them.",
+                "--move".bright_cyan(),
+                REMOVED_DIR_NAME.bright_cyan(),
+                "--delete".bright_cyan()
+            );
+        }
+
+        MirrorAction::Move => {
+            let mut moved = 0;
+
+            for file in &removed {
+                let removed_dir = file.parent().unwrap().join(REMOVED_DIR_NAME);
+                let target = removed_dir.join(file.file_name().unwrap());
+
+                if target.exists() {
+                    warn!(
+                        "Not moving {} as a file already exists at its destination",
+                        file.to_string_lossy().bright_magenta()
+                    );
+
+                    continue;
+                }
+
+                fs::create_dir_all(&removed_dir).with_context(|| {
+                    format!(
+                        "Failed to create directory: {}",
+                        removed_dir.to_string_lossy().bright_magenta()
+                    )
+                })?;
+
+                fs::rename(file, target).with_context(|| {
+                    format!(
+                        "Failed to move file: {}",
+                        file.to_string_lossy().bright_magenta()
+                    )
+                })?;
+
+                moved += 1;
+            }
+
+            success!("Moved {} files.", moved.to_string().bright_yellow());
+        }
+
+        MirrorAction::Delete => {
+            if !confirm_deletion(removed.len(), yes)? {
+                warn!("Aborting deletion.");
+                return Ok(());
+            }
+
+            for file in &removed {
+                fs::remove_file(file).with_context(|| {
+                    format!(
+                        "Failed to delete file: {}",
+                        file.to_string_lossy().bright_magenta()
+                    )
+                })?;
+            }
+
+            success!(
+                "Deleted {} files.",
+                removed.len().to_string().bright_yellow()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Files of a directory whose video was removed from the playlists
+struct RemovedFiles {
+    /// Video files, along with the files sharing their name (e.g. subtitles)
+    files: Vec<PathBuf>,
+
+    /// Number of removed videos
+    videos: usize,
+
+    /// Number of videos in the directory
+    total_videos: usize,
+}
+
+/// Find the files of a directory whose video isn't in the provided list, along with the files sharing their name
+/// (e.g. subtitles)
+fn find_removed_files(dir: &Path, ids: &HashSet<String>) -> Result<RemovedFiles> {
+    let local_videos = list_videos_with_sidecars(dir)?;
+
+    let mut removed = vec![];
+    let mut videos = 0;
+
+    for video in &local_videos {
+        if video.ids.iter().any(|id| ids.contains(id)) {
+            continue;
+        }
+
+        videos += 1;
+
+        for related in video.files() {
+            let path = dir.join(related);
+
+            if !removed.contains(&path) {
+                removed.push(path);
+            }
+        }
+    }
+
+    Ok(RemovedFiles {
+        files: removed,
+        videos,
+        total_videos: local_videos.len(),
+    })
+}
+
+fn confirm_deletion(files: usize, yes: bool) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+
+    if !io::stdin().is_terminal() {
+        bail!(
+            "Refusing to delete files without confirmation, use {} to proceed anyway",
+            "--yes".bright_cyan()
+        );
+    }
+
+    Confirm::new(&format!("Permanently delete these {files} files?"))
+        .with_default(false)
+        .prompt()
+        .context("Failed to setup or retrieve confirmation prompt")
+}
+
+static REMOVED_DIR_NAME: &str = "_removed";
+
+/// Share of a directory's videos above which files are only removed with `--yes`
+static MAX_REMOVED_RATIO: f64 = 0.5;
@@ -1,8 +1,21 @@
 mod actions;
 mod blacklist;
+mod blacklist_actions;
+mod blacklist_rules;
 mod builder;
 mod cache;
 mod cmd;
 mod display;
+mod index;
+mod lock;
+mod m3u;
+mod mirror;
+mod renumber;
+mod retention;
+mod settings;
+mod show_metadata;
+mod state;
+mod status;
+mod subtitles;
 
 pub use self::{actions::sync, builder::build_approximate_index, cmd::SyncArgs};
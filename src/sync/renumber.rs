@@ -0,0 +1,134 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use pomsky_macro::pomsky;
+use regex::Regex;
+
+use crate::{config::Config, dl::PositionInPlaylist, info, success, warn};
+
+use super::{
+    builder::{fetch_playlists, find_playlists, list_videos_with_sidecars},
+    lock::SyncLock,
+};
+
+/// Rename the files of the directories using index prefixes to match the videos' current position in their playlist
+pub fn renumber(dry_run: bool, config: &Config, sync_dir: &Path) -> Result<()> {
+    let _lock = SyncLock::acquire(sync_dir, false, config)?;
+
+    let playlists = find_playlists(sync_dir, config)?
+        .into_iter()
+        .filter(|playlist| playlist.settings.dl_options.index_prefix == Some(true))
+        .collect::<Vec<_>>();
+
+    if playlists.is_empty() {
+        warn!("No synchronization directory uses index prefixes.");
+        return Ok(());
+    }
+
+    info!(
+        "Found {} playlist(s) using index prefixes.",
+        playlists.len().to_string().bright_yellow()
+    );
+
+    let mut positions = HashMap::<PathBuf, HashMap<String, PositionInPlaylist>>::new();
+
+    for video in fetch_playlists(playlists, false, config)? {
+        positions
+            .entry(video.sync_dir)
+            .or_default()
+            .insert(video.id, video.position);
+    }
+
+    let mut dirs = positions.into_iter().collect::<Vec<_>>();
+    dirs.sort_by_key(|(dir, _)| dir.to_string_lossy().to_lowercase());
+
+    let mut renamed = 0;
+
+    for (dir, positions) in dirs {
+        renamed += renumber_dir(&sync_dir.join(&dir), &dir, &positions, dry_run)?;
+    }
+
+    if dry_run {
+        info!(
+            "Found {} files to rename.",
+            renamed.to_string().bright_yellow()
+        );
+    } else {
+        success!("Renamed {} files.", renamed.to_string().bright_yellow());
+    }
+
+    Ok(())
+}
+
+/// Renumber the files of a directory, along with the files sharing their name (e.g. subtitles)
+fn renumber_dir(
+    path: &Path,
+    dir: &Path,
+    positions: &HashMap<String, PositionInPlaylist>,
+    dry_run: bool,
+) -> Result<usize> {
+    let mut renamed = 0;
+
+    for video in list_videos_with_sidecars(path)? {
+        let Some(position) = video.ids.iter().find_map(|id| positions.get(id)) else {
+            continue;
+        };
+
+        let stem = video.stem.as_str();
+
+        let unprefixed = match INDEX_PREFIX_REGEX.find(stem) {
+            Some(prefix) => &stem[prefix.end()..],
+            None => stem,
+        };
+
+        let new_stem = format!("{}{unprefixed}", position.filename_prefix());
+
+        if new_stem == stem {
+            continue;
+        }
+
+        // Rename the media file and its sidecar files (thumbnails, subtitles, ...)
+        for related in video.files() {
+            let new_name = format!("{new_stem}{}", &related[stem.len()..]);
+
+            info!(
+                "{} {} => {}",
+                dir.to_string_lossy().bright_cyan(),
+                related.bright_magenta(),
+                new_name.bright_green()
+            );
+
+            if dry_run {
+                renamed += 1;
+                continue;
+            }
+
+            let target = path.join(&new_name);
+
+            if target.exists() {
+                warn!(
+                    "Not renaming {} as a file already exists at its destination",
+                    related.bright_magenta()
+                );
+
+                continue;
+            }
+
+            fs::rename(path.join(related), target)
+                .with_context(|| format!("Failed to rename file: {}", related.bright_magenta()))?;
+
+            renamed += 1;
+        }
+    }
+
+    Ok(renamed)
+}
+
+static INDEX_PREFIX_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(pomsky!(Start[digit] + ". ")).unwrap());
@@ -0,0 +1,284 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::{
+    config::Config,
+    info,
+    utils::{
+        platforms::{
+            FoundPlatform, build_platform_matchers, determine_video_id_from_platform,
+            try_find_platform,
+        },
+        time::{format_upload_date, now_timestamp},
+    },
+    warn,
+};
+
+use super::{
+    blacklist::{BlacklistEntry, BlacklistEntryDetails, BlacklistReason, blacklist_video},
+    builder::{LocalVideo, list_videos_with_sidecars, parse_url_file},
+    settings::{SyncRetention, SyncSettings},
+};
+
+/// Remove the files rotated out by the retention policy of each synchronization directory
+///
+/// The files' age is determined using their modification time, which is the video's upload date
+/// when the date was repaired. Otherwise, the upload date is read from the video's info JSON or NFO file.
+/// Removed videos are blacklisted under their platform so they aren't downloaded again, so videos whose platform
+/// can't be determined are kept.
+pub fn apply_retention(playlists: &[PathBuf], config: &Config, sync_dir: &Path) -> Result<()> {
+    let platform_matchers = build_platform_matchers(config)?;
+
+    for playlist in playlists {
+        let dir = sync_dir.join(playlist);
+
+        let SyncSettings {
+            retention,
+            dl_options,
+            ..
+        } = SyncSettings::load(&dir, config)?;
+
+        if !retention.is_enabled() {
+            continue;
+        }
+
+        let urls = parse_url_file(
+            &fs::read_to_string(dir.join(&config.url_filename))
+                .context("Failed to read the synchronization file")?,
+        )?;
+
+        let mut platforms: Vec<FoundPlatform> = vec![];
+        let mut platform_skips_repair_date = false;
+
+        for url in &urls {
+            if let Some(platform) = try_find_platform(url, config, &platform_matchers)? {
+                platform_skips_repair_date |=
+                    platform.platform_config.dl_options.skip_repair_date == Some(true);
+
+                if !platforms
+                    .iter()
+                    .any(|found| found.platform_name == platform.platform_name)
+                {
+                    platforms.push(platform);
+                }
+            }
+        }
+
+        let dates_repaired = !dl_options
+            .skip_repair_date
+            .unwrap_or(platform_skips_repair_date);
+
+        let expired = find_expired_files(&dir, &retention, dates_repaired)?;
+
+        if expired.is_empty() {
+            continue;
+        }
+
+        let mut removed = 0;
+
+        for file in &expired {
+            // With multiple platforms, the video's ID must only be valid for one of them
+            let mut candidates = platforms.iter().filter(|platform| {
+                platforms.len() == 1
+                    || determine_video_id_from_platform(
+                        &format!("{}{}", platform.platform_config.videos_url_prefix, file.id),
+                        platform.platform_matchers,
+                    )
+                    .is_some_and(|found| found == file.id)
+            });
+
+            let (Some(platform), None) = (candidates.next(), candidates.next()) else {
+                warn!(
+                    "| Failed to determine the platform of {}, not removing it as it couldn't be blacklisted",
+                    file.filename.bright_magenta()
+                );
+
+                continue;
+            };
+
+            info!(
+                "| Removing {} (retention policy)",
+                file.filename.bright_magenta()
+            );
+
+            for related in &file.related {
+                fs::remove_file(dir.join(related)).with_context(|| {
+                    format!("Failed to remove file: {}", related.bright_magenta())
+                })?;
+            }
+
+            blacklist_video(
+                &dir.join(&config.auto_blacklist_filename),
+                &BlacklistEntry::with_details(
+                    platform.platform_name.to_owned(),
+                    file.id.clone(),
+                    BlacklistEntryDetails {
+                        reason: Some(BlacklistReason::Retention),
+                        added_at: Some(now_timestamp()),
+                        title: Some(file.title.clone()),
+                        comment: None,
+                    },
+                ),
+            )?;
+
+            removed += 1;
+        }
+
+        if removed == 0 {
+            continue;
+        }
+
+        info!(
+            "Removed {} videos from {} following its retention policy.",
+            removed.to_string().bright_yellow(),
+            playlist.to_string_lossy().bright_cyan()
+        );
+    }
+
+    Ok(())
+}
+
+struct ExpiredFile {
+    filename: String,
+    id: String,
+    title: String,
+
+    /// Files to remove, including the media file itself and the files sharing its name (e.g. subtitles)
+    related: Vec<String>,
+}
+
+struct MediaFile {
+    video: LocalVideo,
+    modified: SystemTime,
+
+    /// Upload date as `YYYYMMDD`, only determined if the files' date wasn't repaired
+    upload_date: Option<String>,
+}
+
+fn find_expired_files(
+    dir: &Path,
+    retention: &SyncRetention,
+    dates_repaired: bool,
+) -> Result<Vec<ExpiredFile>> {
+    let SyncRetention { max_files, max_age } = retention;
+
+    let mut media_files = vec![];
+
+    for video in list_videos_with_sidecars(dir)? {
+        let modified = fs::metadata(dir.join(&video.filename))
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| {
+                format!(
+                    "Failed to get modification time of file: {}",
+                    video.filename.bright_magenta()
+                )
+            })?;
+
+        let upload_date = if dates_repaired {
+            None
+        } else {
+            find_upload_date(dir, &video.stem)
+        };
+
+        media_files.push(MediaFile {
+            video,
+            modified,
+            upload_date,
+        });
+    }
+
+    // Newest first, using the modification time unless the upload date of every file is known
+    if media_files.iter().all(|file| file.upload_date.is_some()) {
+        media_files.sort_by(|a, b| b.upload_date.cmp(&a.upload_date));
+    } else {
+        media_files.sort_by_key(|file| std::cmp::Reverse(file.modified));
+    }
+
+    let undated = media_files
+        .iter()
+        .filter(|file| !dates_repaired && file.upload_date.is_none())
+        .count();
+
+    if max_age.is_some() && undated > 0 {
+        warn!(
+            "Upload date of {} files in {} is unknown as their date wasn't repaired, they won't be removed based on their age",
+            undated.to_string().bright_yellow(),
+            dir.to_string_lossy().bright_cyan()
+        );
+    }
+
+    let now = SystemTime::now();
+
+    let min_upload_date = max_age
+        .map(|max_age| format_upload_date(now_timestamp().saturating_sub(max_age.as_secs())));
+
+    let mut expired = vec![];
+
+    for (
+        i,
+        MediaFile {
+            video,
+            modified,
+            upload_date,
+        },
+    ) in media_files.into_iter().enumerate()
+    {
+        let too_many = max_files.is_some_and(|max_files| i >= max_files);
+
+        let too_old = match (&upload_date, &min_upload_date) {
+            (_, None) => false,
+            (Some(upload_date), Some(min_upload_date)) => upload_date < min_upload_date,
+            // The modification time is only meaningful if the date was repaired
+            (None, Some(_)) => {
+                dates_repaired
+                    && max_age.is_some_and(|max_age| {
+                        now.duration_since(modified).is_ok_and(|age| age > max_age)
+                    })
+            }
+        };
+
+        if !too_many && !too_old {
+            continue;
+        }
+
+        expired.push(ExpiredFile {
+            related: video.files().cloned().collect(),
+            title: video.stem.clone(),
+            id: video.id().to_owned(),
+            filename: video.filename,
+        });
+    }
+
+    Ok(expired)
+}
+
+/// Informations used from YT-DLP's info JSON files
+#[derive(Deserialize)]
+struct UploadDateInfoJson {
+    upload_date: Option<String>,
+}
+
+/// Find the upload date of a video from its info JSON or NFO file, as `YYYYMMDD`
+fn find_upload_date(dir: &Path, stem: &str) -> Option<String> {
+    if let Ok(content) = fs::read_to_string(dir.join(format!("{stem}.info.json")))
+        && let Ok(UploadDateInfoJson {
+            upload_date: Some(upload_date),
+        }) = serde_json::from_str(&content)
+    {
+        return Some(upload_date);
+    }
+
+    let content = fs::read_to_string(dir.join(format!("{stem}.nfo"))).ok()?;
+
+    let (_, aired) = content.split_once("<aired>")?;
+    let (aired, _) = aired.split_once("</aired>")?;
+
+    Some(aired.replace('-', ""))
+}
@@ -0,0 +1,289 @@
+use std::{fs, path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    config::{Config, UseCookiesFrom},
+    dl::{CodecPreferences, QualitySpec, SingleDlArgs, SubtitlesOptions},
+    utils::time::{format_upload_date, now_timestamp, parse_duration, parse_upload_date},
+};
+
+use super::cache::PlatformVideo;
+
+/// Settings of a synchronization directory, read from an optional file next to its URL file
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SyncSettings {
+    /// Filters restricting which videos of the playlist are synchronized
+    #[serde(default)]
+    pub filters: SyncFilters,
+
+    /// Download options, overriding the platform's ones
+    #[serde(default)]
+    pub dl_options: SyncDownloadOptions,
+
+    /// Policy removing older videos after each synchronization
+    #[serde(default)]
+    pub retention: SyncRetention,
+}
+
+impl SyncSettings {
+    pub fn load(dir: &Path, config: &Config) -> Result<Self> {
+        let path = dir.join(&config.settings_filename);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let settings = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Failed to read synchronization settings file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })?;
+
+        serde_json::from_str(&settings).with_context(|| {
+            format!(
+                "Failed to decode synchronization settings file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SyncFilters {
+    /// Only keep videos uploaded on or after this date (e.g. "2024-01-31")
+    #[serde(default, deserialize_with = "deserialize_upload_date")]
+    pub min_upload_date: Option<String>,
+
+    /// Only keep videos uploaded on or before this date (e.g. "2024-01-31")
+    #[serde(default, deserialize_with = "deserialize_upload_date")]
+    pub max_upload_date: Option<String>,
+
+    /// Only keep videos lasting at least this duration (e.g. "2m")
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub min_duration: Option<Duration>,
+
+    /// Only keep videos lasting at most this duration (e.g. "1h")
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub max_duration: Option<Duration>,
+
+    /// Only keep the latest videos of the playlist
+    pub max_videos: Option<usize>,
+}
+
+impl SyncFilters {
+    /// Check if videos are filtered on their upload date
+    pub fn uses_upload_date(&self) -> bool {
+        self.min_upload_date.is_some() || self.max_upload_date.is_some()
+    }
+
+    /// Filter the videos of a playlist
+    ///
+    /// Videos missing the information required by a filter are kept. The latest videos are determined
+    /// using their upload date, so the maximum number of videos is only applied if all of them have one.
+    pub fn apply(&self, mut videos: Vec<PlatformVideo>) -> Vec<PlatformVideo> {
+        let Self {
+            min_upload_date,
+            max_upload_date,
+            min_duration,
+            max_duration,
+            max_videos,
+        } = self;
+
+        videos.retain(|video| {
+            let date = video.raw.upload_date.as_deref();
+            let duration = video
+                .raw
+                .duration
+                .map(|duration| Duration::from_secs_f64(duration.max(0.0)));
+
+            date.is_none_or(|date| {
+                min_upload_date.as_deref().is_none_or(|min| date >= min)
+                    && max_upload_date.as_deref().is_none_or(|max| date <= max)
+            }) && duration.is_none_or(|duration| {
+                min_duration.is_none_or(|min| duration >= min)
+                    && max_duration.is_none_or(|max| duration <= max)
+            })
+        });
+
+        if let Some(max_videos) = *max_videos
+            && videos.iter().all(|video| video.raw.upload_date.is_some())
+        {
+            videos.sort_by(|a, b| b.raw.upload_date.cmp(&a.raw.upload_date));
+            videos.truncate(max_videos);
+        }
+
+        videos
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SyncRetention {
+    /// Only keep the newest files
+    pub max_files: Option<usize>,
+
+    /// Only keep files newer than this age (e.g. "30d")
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub max_age: Option<Duration>,
+}
+
+impl SyncRetention {
+    pub fn is_enabled(&self) -> bool {
+        let Self { max_files, max_age } = self;
+
+        max_files.is_some() || max_age.is_some()
+    }
+
+    /// Filters preventing the download of videos which would be removed right after
+    pub fn as_filters(&self) -> SyncFilters {
+        let Self { max_files, max_age } = self;
+
+        SyncFilters {
+            min_upload_date: max_age.map(|max_age| {
+                format_upload_date(now_timestamp().saturating_sub(max_age.as_secs()))
+            }),
+            max_videos: *max_files,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SyncDownloadOptions {
+    /// Quality, either a preset (e.g. "Best720p") or a custom quality expression
+    pub quality: Option<QualitySpec>,
+
+    /// Raw YT-DLP download format (e.g. "bestaudio")
+    pub raw_format: Option<String>,
+
+    /// Custom YT-DLP filenaming
+    pub filenaming: Option<String>,
+
+    /// Prefix filenames with the videos' position in the playlist (e.g. '01. <rest of the filename>')
+    pub index_prefix: Option<bool>,
+
+    /// Bandwidth limit (e.g. "20M" for 20 MB/s)
+    pub bandwidth_limit: Option<String>,
+
+    /// Use cookies from the provided browser or file
+    pub cookies: Option<UseCookiesFrom>,
+
+    /// Disable repairing the videos' date
+    pub skip_repair_date: Option<bool>,
+
+    /// Disable thumbnail downloading and embedding
+    pub no_thumbnail: Option<bool>,
+
+    /// Slow down requests
+    pub rate_limited: Option<bool>,
+
+    /// Write NFO files for media servers
+    pub write_nfo: Option<bool>,
+
+    /// Check the videos' availability before downloading them
+    pub needs_checking: Option<bool>,
+
+    /// Maximum number of videos downloaded at once from the platform
+    pub max_parallel_downloads: Option<usize>,
+
+    /// Subtitles to download
+    pub subtitles: Option<SubtitlesOptions>,
+
+    /// Output format (e.g. "mkv")
+    pub output_format: Option<String>,
+
+    /// Codec and container preferences for quality presets
+    pub codec_preferences: Option<CodecPreferences>,
+
+    /// Additional arguments to forward to YT-DLP (after the platform's ones)
+    pub forward_ytdlp_args: Option<Vec<String>>,
+}
+
+impl SyncDownloadOptions {
+    /// Apply the overrides to the download arguments of a video
+    pub fn apply(&self, args: SingleDlArgs) -> SingleDlArgs {
+        let Self {
+            quality,
+            raw_format,
+            filenaming,
+            index_prefix,
+            bandwidth_limit,
+            cookies,
+            skip_repair_date,
+            no_thumbnail,
+            rate_limited,
+            write_nfo,
+            // Used when building the synchronization cache
+            needs_checking: _,
+            max_parallel_downloads,
+            subtitles,
+            output_format,
+            codec_preferences,
+            forward_ytdlp_args,
+        } = self;
+
+        let (quality, custom_quality) = match quality {
+            Some(QualitySpec::Preset(quality)) => (Some(*quality), None),
+            Some(QualitySpec::Custom(quality)) => (None, Some(quality.clone())),
+            None => (args.quality, args.custom_quality),
+        };
+
+        let (subs, auto_subs, embed_subs, subs_format) = match subtitles {
+            Some(SubtitlesOptions {
+                languages,
+                auto_generated,
+                embed,
+                format,
+            }) => (languages.clone(), *auto_generated, *embed, *format),
+            None => (args.subs, args.auto_subs, args.embed_subs, args.subs_format),
+        };
+
+        SingleDlArgs {
+            quality,
+            custom_quality,
+            raw_format: raw_format.clone().or(args.raw_format),
+            filenaming: filenaming.clone().or(args.filenaming),
+            index_prefix: index_prefix.or(args.index_prefix),
+            limit_bandwidth: bandwidth_limit.clone().or(args.limit_bandwidth),
+            cookies: cookies.clone().or(args.cookies),
+            skip_repair_date: skip_repair_date.or(args.skip_repair_date),
+            no_thumbnail: no_thumbnail.or(args.no_thumbnail),
+            rate_limited: rate_limited.or(args.rate_limited),
+            write_nfo: write_nfo.or(args.write_nfo),
+            max_parallel_downloads: max_parallel_downloads.or(args.max_parallel_downloads),
+            subs,
+            auto_subs,
+            embed_subs,
+            subs_format,
+            output_format: output_format.clone().or(args.output_format),
+            codec_preferences: codec_preferences.clone().or(args.codec_preferences),
+            forward_ytdlp_args: forward_ytdlp_args
+                .clone()
+                .unwrap_or(args.forward_ytdlp_args),
+            ..args
+        }
+    }
+}
+
+fn deserialize_upload_date<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|date| parse_upload_date(&date).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|duration| parse_duration(&duration).map_err(serde::de::Error::custom))
+        .transpose()
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{Config, UseCookiesFrom},
+    utils::{
+        nfo::{build_show_nfo, write_nfo},
+        platforms::{build_platform_matchers, try_find_platform},
+        ytdlp::{RawPlaylistInfos, download_playlist_thumbnail},
+    },
+    warn,
+};
+
+use super::builder::PlaylistUrl;
+
+/// Synchronization directory for which media server metadata (show NFO and poster) must be written
+#[derive(Serialize, Deserialize)]
+pub struct ShowMetadataTarget {
+    /// URL of the directory's first playlist
+    url: String,
+    cookies: Option<UseCookiesFrom>,
+
+    /// Metadata of the directory's first playlist, once fetched
+    pub infos: Option<RawPlaylistInfos>,
+}
+
+/// Find the synchronization directories with NFO files enabled, either by their settings or their platform
+pub fn find_show_metadata_targets(
+    playlists: &[PlaylistUrl],
+    config: &Config,
+) -> Result<HashMap<PathBuf, ShowMetadataTarget>> {
+    let platform_matchers = build_platform_matchers(config)?;
+
+    let mut targets = HashMap::new();
+
+    for playlist in playlists {
+        let dl_options = &playlist.settings.dl_options;
+
+        let mut enabled_by_platform = false;
+        let mut cookies = dl_options.cookies.clone();
+
+        for url in &playlist.urls {
+            if let Some(platform) = try_find_platform(url, config, &platform_matchers)? {
+                let platform_dl_options = &platform.platform_config.dl_options;
+
+                enabled_by_platform |= platform_dl_options.write_nfo == Some(true);
+
+                if cookies.is_none() {
+                    cookies = platform_dl_options.cookies.clone();
+                }
+            }
+        }
+
+        // The directory's settings take precedence over the platforms' ones
+        if dl_options.write_nfo.unwrap_or(enabled_by_platform) {
+            targets.insert(
+                playlist.sync_dir.clone(),
+                ShowMetadataTarget {
+                    url: playlist.urls[0].clone(),
+                    cookies,
+                    infos: None,
+                },
+            );
+        }
+    }
+
+    Ok(targets)
+}
+
+/// Write the show NFO file and download the poster of each target directory
+///
+/// The poster is only downloaded if it doesn't exist yet, and failing to download it isn't fatal.
+pub fn write_show_metadata(
+    targets: &HashMap<PathBuf, ShowMetadataTarget>,
+    config: &Config,
+    sync_dir: &Path,
+) -> Result<()> {
+    for (
+        dir,
+        ShowMetadataTarget {
+            url,
+            cookies,
+            infos,
+        },
+    ) in targets
+    {
+        let Some(infos) = infos else {
+            continue;
+        };
+
+        let path = sync_dir.join(dir);
+
+        match build_show_nfo(infos, url) {
+            Some(nfo) => write_nfo(&path.join(SHOW_NFO_FILENAME), &nfo)?,
+            None => warn!(
+                "Playlist of {} doesn't have a title, not writing its show NFO file",
+                dir.to_string_lossy().bright_cyan()
+            ),
+        }
+
+        if path.join(format!("{POSTER_FILENAME}.jpg")).exists() {
+            continue;
+        }
+
+        if let Err(err) = download_playlist_thumbnail(
+            &config.yt_dlp_bin,
+            url,
+            &path.join(POSTER_FILENAME),
+            cookies.as_ref(),
+        ) {
+            warn!(
+                "Failed to download the poster of {}: {err:?}",
+                dir.to_string_lossy().bright_cyan()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+static SHOW_NFO_FILENAME: &str = "tvshow.nfo";
+static POSTER_FILENAME: &str = "poster";
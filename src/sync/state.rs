@@ -0,0 +1,57 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+
+use super::{m3u::PlaylistTrack, subtitles::SubtitlesUnavailable};
+
+/// State of a synchronization directory, persisted next to its URL file
+#[derive(Serialize, Deserialize, Default)]
+pub struct SyncState {
+    /// UNIX timestamp (in seconds) of the last successful synchronization
+    pub last_sync: Option<u64>,
+
+    /// Videos of the remote playlist(s) as of the last fetch, in order
+    #[serde(default)]
+    pub playlist: Vec<PlaylistTrack>,
+
+    /// Videos without any available subtitles, by ID
+    #[serde(default)]
+    pub subtitles_unavailable: HashMap<String, SubtitlesUnavailable>,
+}
+
+impl SyncState {
+    pub fn load(dir: &Path, config: &Config) -> Result<Self> {
+        let path = dir.join(&config.state_filename);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let state = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "Failed to read synchronization state file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })?;
+
+        serde_json::from_str(&state).with_context(|| {
+            format!(
+                "Failed to decode synchronization state file at path: {}",
+                path.to_string_lossy().bright_magenta()
+            )
+        })
+    }
+
+    pub fn save(&self, dir: &Path, config: &Config) -> Result<()> {
+        fs::write(
+            dir.join(&config.state_filename),
+            serde_json::to_string_pretty(self)
+                .context("Failed to serialize synchronization state")?,
+        )
+        .context("Failed to write synchronization state file")
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::{
+    config::Config,
+    utils::{
+        platforms::{build_platform_matchers, try_find_platform},
+        time::{format_duration, format_timestamp, now_timestamp},
+    },
+};
+
+use super::{
+    blacklist::load_optional_blacklists,
+    builder::{VIDEO_ID_REGEX, find_playlists},
+    cache::Cache,
+    state::SyncState,
+};
+
+#[derive(Serialize)]
+struct PlaylistStatus {
+    sync_dir: PathBuf,
+    urls: Vec<String>,
+    platform: Option<String>,
+    local_files: usize,
+    auto_blacklisted: usize,
+    custom_blacklisted: usize,
+    custom_blacklist_rules: usize,
+    pending_cache: Option<usize>,
+    last_sync: Option<u64>,
+}
+
+pub fn status(json: bool, config: &Config, sync_dir: &Path) -> Result<()> {
+    let platform_matchers = build_platform_matchers(config)?;
+
+    let playlists = find_playlists(sync_dir, config)?;
+
+    let mut caches = HashMap::<PathBuf, Option<Cache>>::new();
+
+    let mut statuses = vec![];
+
+    for playlist in playlists {
+        let dir = sync_dir.join(&playlist.sync_dir);
+
+        let mut platforms = vec![];
+
+        for url in &playlist.urls {
+            if let Ok(Some(platform)) = try_find_platform(url, config, &platform_matchers)
+                && !platforms.contains(&platform.platform_name)
+            {
+                platforms.push(platform.platform_name);
+            }
+        }
+
+        let platform = Some(platforms.join(", ")).filter(|platforms| !platforms.is_empty());
+
+        let auto_blacklisted =
+            load_optional_blacklists(&[&dir.join(&config.auto_blacklist_filename)])?
+                .entries
+                .len();
+
+        let custom_blacklist =
+            load_optional_blacklists(&[&dir.join(&config.custom_blacklist_filename)])?;
+
+        let pending_cache =
+            count_pending_entries(&playlist.sync_dir, &mut caches, config, sync_dir)?;
+
+        let SyncState {
+            last_sync,
+            playlist: _,
+            subtitles_unavailable: _,
+        } = SyncState::load(&dir, config)?;
+
+        statuses.push(PlaylistStatus {
+            local_files: count_local_files(&dir, config)?,
+            sync_dir: playlist.sync_dir,
+            urls: playlist.urls,
+            platform,
+            auto_blacklisted,
+            custom_blacklisted: custom_blacklist.entries.len(),
+            custom_blacklist_rules: custom_blacklist.rules.len(),
+            pending_cache,
+            last_sync,
+        });
+    }
+
+    statuses.sort_by_key(|status| status.sync_dir.to_string_lossy().to_lowercase());
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&statuses).context("Failed to serialize status")?
+        );
+    } else {
+        display_statuses(&statuses);
+    }
+
+    Ok(())
+}
+
+/// Count files with a video ID in a synchronization directory, ignoring nested synchronization directories
+fn count_local_files(dir: &Path, config: &Config) -> Result<usize> {
+    let mut count = 0;
+
+    let mut walker = WalkDir::new(dir).into_iter();
+
+    while let Some(item) = walker.next() {
+        let item = item.context("Failed to read directory entry while counting files")?;
+
+        if item.file_type().is_dir() {
+            if item.depth() > 0 && item.path().join(&config.url_filename).is_file() {
+                walker.skip_current_dir();
+            }
+
+            continue;
+        }
+
+        if item
+            .file_name()
+            .to_str()
+            .is_some_and(|name| VIDEO_ID_REGEX.is_match(name))
+        {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Count the cache entries targeting a synchronization directory, in all caches located in it or its parents
+///
+/// Returns `None` if no cache is pending for this directory.
+fn count_pending_entries(
+    playlist_dir: &Path,
+    caches: &mut HashMap<PathBuf, Option<Cache>>,
+    config: &Config,
+    sync_dir: &Path,
+) -> Result<Option<usize>> {
+    let playlist_dir = normalize(playlist_dir);
+
+    let mut pending = None;
+
+    for cache_dir in playlist_dir.ancestors() {
+        if !caches.contains_key(cache_dir) {
+            let cache_path = sync_dir.join(cache_dir).join(&config.cache_filename);
+
+            let cache = if cache_path.is_file() {
+                Some(Cache::load_from_disk(&cache_path)?)
+            } else {
+                None
+            };
+
+            caches.insert(cache_dir.to_path_buf(), cache);
+        }
+
+        let Some(cache) = &caches[cache_dir] else {
+            continue;
+        };
+
+        let relative = playlist_dir.strip_prefix(cache_dir).unwrap();
+
+        let count = cache
+            .entries
+            .iter()
+            .filter(|entry| normalize(&entry.sync_dir) == relative)
+            .count();
+
+        *pending.get_or_insert(0) += count;
+    }
+
+    Ok(pending)
+}
+
+/// Remove the `.` components from a relative path
+fn normalize(path: &Path) -> PathBuf {
+    path.components().collect()
+}
+
+fn display_statuses(statuses: &[PlaylistStatus]) {
+    let now = now_timestamp();
+
+    let rows = statuses
+        .iter()
+        .map(|status| {
+            let PlaylistStatus {
+                sync_dir,
+                urls,
+                platform,
+                local_files,
+                auto_blacklisted,
+                custom_blacklisted,
+                custom_blacklist_rules,
+                pending_cache,
+                last_sync,
+            } = status;
+
+            [
+                sync_dir.to_string_lossy().to_string(),
+                platform.clone().unwrap_or_else(|| "<unknown>".to_owned()),
+                local_files.to_string(),
+                format!("{auto_blacklisted} / {custom_blacklisted} / {custom_blacklist_rules}"),
+                match pending_cache {
+                    Some(count) => count.to_string(),
+                    None => "-".to_owned(),
+                },
+                match last_sync {
+                    Some(last_sync) => format!(
+                        "{} ({} ago)",
+                        format_timestamp(*last_sync),
+                        format_duration(Duration::from_secs(now.saturating_sub(*last_sync)))
+                    ),
+                    None => "never".to_owned(),
+                },
+                urls.join(" "),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let headers = [
+        "Directory",
+        "Platform",
+        "Files",
+        "Blacklisted (auto / custom / rules)",
+        "Pending",
+        "Last sync",
+        "URL",
+    ];
+
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header.len()])
+                .max()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let pad = |cell: &str, column: usize| format!("{cell:<width$}", width = widths[column]);
+
+    println!(
+        "{}",
+        headers
+            .iter()
+            .enumerate()
+            .map(|(column, header)| if column + 1 < headers.len() {
+                pad(header, column)
+            } else {
+                header.to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .bold()
+    );
+
+    for row in &rows {
+        println!(
+            "{}  {}  {}  {}  {}  {}  {}",
+            pad(&row[0], 0).bright_cyan(),
+            pad(&row[1], 1).bright_yellow(),
+            pad(&row[2], 2).bright_green(),
+            pad(&row[3], 3).bright_red(),
+            pad(&row[4], 4).bright_yellow(),
+            pad(&row[5], 5).bright_black(),
+            row[6].bright_magenta()
+        );
+    }
+}
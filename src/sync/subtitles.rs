@@ -0,0 +1,270 @@
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Config,
+    dl::{SubtitlesOptions, is_subtitles_file},
+    error_anyhow, info, info_inline, success,
+    utils::{
+        interrupt::is_interrupted,
+        platforms::{
+            FoundPlatform, build_platform_matchers, determine_video_id_from_platform,
+            try_find_platform,
+        },
+        time::now_timestamp,
+        ytdlp::download_subtitles,
+    },
+    warn,
+};
+
+use super::{
+    builder::{find_playlists, list_videos_with_sidecars},
+    lock::SyncLock,
+    state::SyncState,
+};
+
+/// Download the missing subtitles of the videos already downloaded in each synchronization directory
+///
+/// Videos are matched using the ID in their filename, and to the platform of one of the directory's playlists.
+/// Only directories with subtitles configured (in their settings or their platform's download options) are treated.
+/// Videos without any available subtitles are remembered, so they aren't queried again with the same options.
+pub fn backfill_subtitles(dry_run: bool, config: &Config, sync_dir: &Path) -> Result<()> {
+    let _lock = SyncLock::acquire(sync_dir, false, config)?;
+
+    let platform_matchers = build_platform_matchers(config)?;
+
+    let mut playlists = find_playlists(sync_dir, config)?;
+    playlists.sort_by_key(|playlist| playlist.sync_dir.to_string_lossy().to_lowercase());
+
+    let mut downloaded = 0;
+    let mut missing = 0;
+
+    for playlist in playlists {
+        let dir = playlist.sync_dir.to_string_lossy();
+        let dl_options = &playlist.settings.dl_options;
+
+        let mut platforms: Vec<(FoundPlatform, _, _)> = vec![];
+
+        for url in &playlist.urls {
+            let Some(platform) = try_find_platform(url, config, &platform_matchers)? else {
+                continue;
+            };
+
+            if platforms
+                .iter()
+                .any(|(other, _, _)| other.platform_name == platform.platform_name)
+            {
+                continue;
+            }
+
+            let platform_dl_options = &platform.platform_config.dl_options;
+
+            let Some(subtitles) = dl_options
+                .subtitles
+                .as_ref()
+                .or(platform_dl_options.subtitles.as_ref())
+            else {
+                continue;
+            };
+
+            let cookies = dl_options
+                .cookies
+                .as_ref()
+                .or(platform_dl_options.cookies.as_ref());
+
+            platforms.push((platform, subtitles, cookies));
+        }
+
+        if platforms.is_empty() {
+            continue;
+        }
+
+        if platforms.iter().any(|(_, subtitles, _)| subtitles.embed) {
+            warn!(
+                "Skipping {} as its subtitles are embedded, which can't be done for existing files",
+                dir.bright_cyan()
+            );
+
+            continue;
+        }
+
+        let path = sync_dir.join(&playlist.sync_dir);
+        let mut state = SyncState::load(&path, config)?;
+
+        let mut videos = vec![];
+        let mut unavailable = 0;
+
+        for (stem, id) in find_videos_without_subtitles(&path)? {
+            // With multiple platforms, the video's ID must only be valid for one of them
+            let mut candidates = platforms.iter().filter(|(platform, _, _)| {
+                platforms.len() == 1
+                    || determine_video_id_from_platform(
+                        &format!("{}{id}", platform.platform_config.videos_url_prefix),
+                        platform.platform_matchers,
+                    )
+                    .is_some_and(|found| found == id)
+            });
+
+            let (Some(platform), None) = (candidates.next(), candidates.next()) else {
+                warn!(
+                    "| Failed to determine the platform of {}, skipping it",
+                    stem.bright_magenta()
+                );
+
+                continue;
+            };
+
+            let (_, subtitles, _) = platform;
+
+            if state
+                .subtitles_unavailable
+                .get(&id)
+                .is_some_and(|record| record.matches(subtitles))
+            {
+                unavailable += 1;
+                continue;
+            }
+
+            videos.push((stem, id, platform));
+        }
+
+        if unavailable > 0 {
+            info!(
+                "Skipping {} videos without available subtitles in {}",
+                unavailable.to_string().bright_yellow(),
+                dir.bright_cyan()
+            );
+        }
+
+        if videos.is_empty() {
+            continue;
+        }
+
+        info!(
+            "Found {} videos without subtitles in {}",
+            videos.len().to_string().bright_yellow(),
+            dir.bright_cyan()
+        );
+
+        missing += videos.len();
+
+        if dry_run {
+            for (stem, _, _) in &videos {
+                info!("| {}", stem.bright_magenta());
+            }
+
+            continue;
+        }
+
+        for (stem, id, (platform, subtitles, cookies)) in videos {
+            info_inline!("| Downloading subtitles for {}... ", stem.bright_magenta());
+
+            let url = format!("{}{id}", platform.platform_config.videos_url_prefix);
+
+            let subtitles_args = SubtitlesOptions {
+                embed: false,
+                ..(*subtitles).clone()
+            }
+            .to_yt_dlp_args();
+
+            let result = download_subtitles(
+                &config.yt_dlp_bin,
+                &url,
+                &path.join(&stem),
+                &subtitles_args,
+                *cookies,
+            );
+
+            if is_interrupted() {
+                state.save(&path, config)?;
+                bail!("Subtitles downloading was interrupted");
+            }
+
+            match result {
+                Err(err) => {
+                    error_anyhow!(err);
+                }
+
+                Ok(()) if has_subtitles(&path, &stem)? => {
+                    success!("OK");
+                    downloaded += 1;
+                }
+
+                Ok(()) => {
+                    warn!("none available");
+
+                    state
+                        .subtitles_unavailable
+                        .insert(id, SubtitlesUnavailable::new(subtitles));
+                }
+            }
+        }
+
+        state.save(&path, config)?;
+    }
+
+    if dry_run {
+        info!(
+            "Found {} videos without subtitles.",
+            missing.to_string().bright_yellow()
+        );
+    } else if missing == 0 {
+        success!("No subtitles to download!");
+    } else {
+        success!(
+            "Downloaded subtitles for {} of {} videos.",
+            downloaded.to_string().bright_yellow(),
+            missing.to_string().bright_yellow()
+        );
+    }
+
+    Ok(())
+}
+
+/// Record of a video for which no subtitles were available
+#[derive(Serialize, Deserialize)]
+pub struct SubtitlesUnavailable {
+    languages: Vec<String>,
+    auto_generated: bool,
+
+    /// UNIX timestamp (in seconds) of the check
+    checked_at: u64,
+}
+
+impl SubtitlesUnavailable {
+    fn new(options: &SubtitlesOptions) -> Self {
+        Self {
+            languages: options.languages.clone(),
+            auto_generated: options.auto_generated,
+            checked_at: now_timestamp(),
+        }
+    }
+
+    /// Check if the video was checked for the same subtitles
+    fn matches(&self, options: &SubtitlesOptions) -> bool {
+        self.languages == options.languages && self.auto_generated == options.auto_generated
+    }
+}
+
+/// Find the videos of a directory without any subtitles file sharing their name, along with their ID
+fn find_videos_without_subtitles(dir: &Path) -> Result<Vec<(String, String)>> {
+    let videos = list_videos_with_sidecars(dir)?
+        .into_iter()
+        .filter(|video| !video.sidecars.iter().any(|name| is_subtitles_file(name)))
+        // Use the complete ID found in the filename
+        .map(|video| (video.stem.clone(), video.id().to_owned()))
+        .collect();
+
+    Ok(videos)
+}
+
+fn has_subtitles(dir: &Path, stem: &str) -> Result<bool> {
+    let has_subtitles = list_videos_with_sidecars(dir)?
+        .iter()
+        .filter(|video| video.stem == stem)
+        .any(|video| video.sidecars.iter().any(|name| is_subtitles_file(name)));
+
+    Ok(has_subtitles)
+}
@@ -0,0 +1,162 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+
+/// Move a file to its final destination, without ever exposing a partially-written file under the destination's name
+///
+/// The file is renamed if possible. When moving across filesystems, it is first copied to a hidden temporary file
+/// next to the destination (see [`partial_file_path`]), synced to disk, then renamed to its final name.
+/// In both cases, the destination directory is synced to disk afterwards.
+///
+/// Like [`fs::rename`], an existing file at the destination is replaced, so callers must check for it beforehand
+/// if it must be kept.
+pub fn move_file(from: &Path, to: &Path) -> Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => return sync_parent_dir(to),
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {}
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!(
+                    "Failed to move file {} to {}",
+                    from.to_string_lossy().bright_magenta(),
+                    to.to_string_lossy().bright_magenta()
+                )
+            });
+        }
+    }
+
+    let tmp_file = partial_file_path(to)?;
+
+    if let Err(err) = copy_and_sync(from, &tmp_file) {
+        // Don't leave a partial file behind
+        let _ = fs::remove_file(&tmp_file);
+
+        return Err(err).with_context(|| {
+            format!(
+                "Failed to copy file {} to {}",
+                from.to_string_lossy().bright_magenta(),
+                tmp_file.to_string_lossy().bright_magenta()
+            )
+        });
+    }
+
+    fs::rename(&tmp_file, to).with_context(|| {
+        format!(
+            "Failed to rename copied file {} to {}",
+            tmp_file.to_string_lossy().bright_magenta(),
+            to.to_string_lossy().bright_magenta()
+        )
+    })?;
+
+    sync_parent_dir(to)?;
+
+    fs::remove_file(from).with_context(|| {
+        format!(
+            "Failed to remove moved file at path: {}",
+            from.to_string_lossy().bright_magenta()
+        )
+    })
+}
+
+/// Replace the content of a file, without ever leaving it partially written (e.g. if the process crashes)
+///
+/// The content is written to a hidden temporary file next to it, synced to disk, then renamed to the file's name.
+pub fn write_file_atomically(path: &Path, content: &str) -> Result<()> {
+    let tmp_file = partial_file_path(path)?;
+
+    if let Err(err) = write_and_sync(&tmp_file, content) {
+        // Don't leave a partial file behind
+        let _ = fs::remove_file(&tmp_file);
+
+        return Err(err).with_context(|| {
+            format!(
+                "Failed to write file at path: {}",
+                tmp_file.to_string_lossy().bright_magenta()
+            )
+        });
+    }
+
+    fs::rename(&tmp_file, path).with_context(|| {
+        format!(
+            "Failed to rename written file {} to {}",
+            tmp_file.to_string_lossy().bright_magenta(),
+            path.to_string_lossy().bright_magenta()
+        )
+    })?;
+
+    sync_parent_dir(path)
+}
+
+/// Path of the hidden temporary file a file is written to before being renamed to its final name
+///
+/// It doesn't have the media extension, so it's never mistaken for a downloaded video. Such a file is left behind
+/// if the process crashes before the rename.
+pub fn partial_file_path(path: &Path) -> Result<PathBuf> {
+    let filename = path
+        .file_name()
+        .with_context(|| format!("Invalid file path: {}", path.display()))?;
+
+    let mut tmp_filename = OsString::from(".");
+    tmp_filename.push(filename);
+    tmp_filename.push(PARTIAL_FILE_EXTENSION);
+
+    Ok(path.with_file_name(tmp_filename))
+}
+
+/// Sync the directory containing a file to disk, so a rename to this file persists after a crash
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> Result<()> {
+    let Some(dir) = path.parent() else {
+        return Ok(());
+    };
+
+    // An empty parent means the current directory
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+
+    File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .with_context(|| {
+            format!(
+                "Failed to sync directory to disk: {}",
+                dir.to_string_lossy().bright_magenta()
+            )
+        })
+}
+
+/// Directories can't be opened to be synced on this platform
+#[cfg(not(unix))]
+fn sync_parent_dir(_: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn write_and_sync(path: &Path, content: &str) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()
+}
+
+fn copy_and_sync(from: &Path, to: &Path) -> io::Result<()> {
+    fs::copy(from, to)?;
+
+    // Write access is required to sync on some platforms
+    OpenOptions::new().write(true).open(to)?.sync_all()
+}
+
+/// Extensions of the media files, used to recognize downloaded videos
+pub static MEDIA_EXTENSIONS: &[&str] = &[
+    "mp4", "mkv", "webm", "mov", "avi", "flv", "3gp", "m4a", "mp3", "opus", "ogg", "flac", "alac",
+    "aac", "wav", "aiff", "dsf",
+];
+
+/// Extension of the files being copied to their final destination
+static PARTIAL_FILE_EXTENSION: &str = ".ytdl-partial";
@@ -0,0 +1,91 @@
+use std::{
+    process::{Command, Stdio},
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{error, warn};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Handle Ctrl-C by letting the running operations finish or abort cleanly
+///
+/// A second Ctrl-C exits immediately.
+pub fn setup_interrupt_handler() -> Result<()> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            error!("\nInterrupted twice, exiting immediately.");
+            std::process::exit(130);
+        }
+
+        warn!(
+            "\nInterrupted, stopping after cleaning up (press Ctrl-C again to exit immediately)..."
+        );
+    })
+    .context("Failed to setup the interruption handler")
+}
+
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Sleep for the provided duration, stopping early in case of interruption
+///
+/// Returns `false` if the sleep was interrupted
+pub fn sleep_unless_interrupted(duration: Duration) -> bool {
+    let start = Instant::now();
+
+    while !is_interrupted() {
+        let remaining = duration.saturating_sub(start.elapsed());
+
+        if remaining.is_zero() {
+            return true;
+        }
+
+        std::thread::sleep(remaining.min(INTERRUPT_POLLING_INTERVAL));
+    }
+
+    false
+}
+
+/// Wait for a child process using the provided function, forwarding interruptions to it
+///
+/// Children started from a terminal already receive Ctrl-C themselves, so the signal is only
+/// forwarded if the child is still running after a grace period (e.g. when only this process was signaled).
+pub fn forward_interrupt_to_child<T>(child_pid: u32, wait: impl FnOnce() -> T) -> T {
+    let exited = AtomicBool::new(false);
+
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            while !exited.load(Ordering::SeqCst) && !is_interrupted() {
+                std::thread::sleep(INTERRUPT_POLLING_INTERVAL);
+            }
+
+            let interrupted_at = Instant::now();
+
+            while !exited.load(Ordering::SeqCst) {
+                if interrupted_at.elapsed() >= CHILD_INTERRUPT_GRACE_PERIOD {
+                    let _ = Command::new("kill")
+                        .arg("-INT")
+                        .arg(child_pid.to_string())
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .status();
+
+                    return;
+                }
+
+                std::thread::sleep(INTERRUPT_POLLING_INTERVAL);
+            }
+        });
+
+        let result = wait();
+        exited.store(true, Ordering::SeqCst);
+        result
+    })
+}
+
+static INTERRUPT_POLLING_INTERVAL: Duration = Duration::from_millis(100);
+static CHILD_INTERRUPT_GRACE_PERIOD: Duration = Duration::from_secs(2);
@@ -1,6 +1,10 @@
 pub mod filenames;
+pub mod files;
+pub mod interrupt;
 pub mod logging;
+pub mod nfo;
 pub mod platforms;
 pub mod regex;
 pub mod shell;
+pub mod time;
 pub mod ytdlp;
@@ -0,0 +1,188 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Deserialize;
+
+use super::ytdlp::RawPlaylistInfos;
+
+/// Informations on a video, as provided by YT-DLP's info JSON file
+#[derive(Deserialize)]
+struct VideoInfoJson {
+    id: String,
+    title: String,
+    extractor_key: Option<String>,
+    webpage_url: Option<String>,
+    description: Option<String>,
+    uploader: Option<String>,
+    channel: Option<String>,
+
+    /// Upload date as `YYYYMMDD`
+    upload_date: Option<String>,
+
+    /// Duration in seconds
+    duration: Option<f64>,
+
+    tags: Option<Vec<String>>,
+}
+
+/// Write a Kodi-style episode NFO file from a YT-DLP info JSON file
+pub fn write_video_nfo(info_json: &Path, nfo_file: &Path) -> Result<()> {
+    let json = fs::read_to_string(info_json).with_context(|| {
+        format!(
+            "Failed to read info JSON file: {}",
+            info_json.to_string_lossy().bright_magenta()
+        )
+    })?;
+
+    let VideoInfoJson {
+        id,
+        title,
+        extractor_key,
+        webpage_url,
+        description,
+        uploader,
+        channel,
+        upload_date,
+        duration,
+        tags,
+    } = serde_json::from_str(&json).with_context(|| {
+        format!(
+            "Failed to decode info JSON file: {}",
+            info_json.to_string_lossy().bright_magenta()
+        )
+    })?;
+
+    let mut nfo = NfoWriter::new("episodedetails");
+
+    nfo.tag("title", Some(&title));
+    nfo.tag("showtitle", channel.as_ref().or(uploader.as_ref()));
+    nfo.tag("plot", description.as_ref());
+    nfo.tag("studio", uploader.as_ref().or(channel.as_ref()));
+
+    if let Some(date) = upload_date.as_deref().and_then(format_nfo_date) {
+        nfo.tag("aired", Some(&date));
+        nfo.tag("premiered", Some(&date));
+        nfo.tag("year", Some(&date[..4]));
+    }
+
+    // Kodi expects the runtime in minutes
+    if let Some(duration) = duration {
+        nfo.tag(
+            "runtime",
+            Some(&((duration / 60.0).round() as u64).to_string()),
+        );
+    }
+
+    for tag in tags.iter().flatten() {
+        nfo.tag("tag", Some(tag));
+    }
+
+    let id_type = extractor_key.map_or_else(|| "ytdl".to_owned(), |key| key.to_lowercase());
+
+    nfo.raw(&format!(
+        "<uniqueid type=\"{}\" default=\"true\">{}</uniqueid>",
+        escape_xml(&id_type),
+        escape_xml(&id)
+    ));
+
+    nfo.tag("url", webpage_url.as_ref());
+
+    write_nfo(nfo_file, &nfo.finish())
+}
+
+/// Build a Kodi-style TV show NFO file from a playlist's metadata
+///
+/// Returns `None` if the playlist doesn't have a title.
+pub fn build_show_nfo(playlist: &RawPlaylistInfos, url: &str) -> Option<String> {
+    let RawPlaylistInfos {
+        title,
+        description,
+        uploader,
+        channel,
+    } = playlist;
+
+    let mut nfo = NfoWriter::new("tvshow");
+
+    nfo.tag("title", Some(title.as_ref()?));
+    nfo.tag("plot", description.as_ref());
+    nfo.tag("studio", uploader.as_ref().or(channel.as_ref()));
+    nfo.tag("url", Some(url));
+
+    Some(nfo.finish())
+}
+
+/// Write an NFO file, unless it already exists with the same content
+pub fn write_nfo(path: &Path, content: &str) -> Result<()> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+
+    fs::write(path, content).with_context(|| {
+        format!(
+            "Failed to write NFO file at path: {}",
+            path.to_string_lossy().bright_magenta()
+        )
+    })
+}
+
+struct NfoWriter {
+    root: &'static str,
+    content: String,
+}
+
+impl NfoWriter {
+    fn new(root: &'static str) -> Self {
+        Self {
+            root,
+            content: format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<{root}>\n"
+            ),
+        }
+    }
+
+    fn tag(&mut self, name: &str, value: Option<impl AsRef<str>>) {
+        if let Some(value) = value {
+            self.raw(&format!("<{name}>{}</{name}>", escape_xml(value.as_ref())));
+        }
+    }
+
+    fn raw(&mut self, line: &str) {
+        self.content.push_str("  ");
+        self.content.push_str(line);
+        self.content.push('\n');
+    }
+
+    fn finish(mut self) -> String {
+        self.content.push_str(&format!("</{}>\n", self.root));
+        self.content
+    }
+}
+
+/// Convert a `YYYYMMDD` date to the `YYYY-MM-DD` format expected by media servers
+fn format_nfo_date(date: &str) -> Option<String> {
+    if date.len() != 8 || !date.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(format!("{}-{}-{}", &date[..4], &date[4..6], &date[6..]))
+}
+
+fn escape_xml(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -1,6 +1,6 @@
 use std::{
     ffi::OsStr,
-    io::{self, Read, Write},
+    io::{self, BufRead, BufReader, Read, Write},
     path::Path,
     process::{Command, ExitStatus, Stdio},
 };
@@ -8,8 +8,9 @@ use std::{
 use anyhow::{bail, Context, Result};
 
 use colored::Colorize;
+use indicatif::ProgressBar;
 
-use crate::fail;
+use crate::{fail, utils::interrupt::forward_interrupt_to_child};
 
 fn flush_stdout() {
     io::stdout()
@@ -64,15 +65,77 @@ pub fn run_custom_cmd_bi_outs(
     let mut stderr_bytes: Vec<u8> = vec![];
     let mut io_stderr = io::stderr();
 
-    let status = loop {
-        let size = stderr.read(&mut readbuf)?;
-        io_stderr.write_all(&readbuf[0..size])?;
-        stderr_bytes.extend(&readbuf[0..size]);
+    let status = forward_interrupt_to_child(child.id(), || -> Result<ExitStatus> {
+        loop {
+            let size = stderr.read(&mut readbuf)?;
+            io_stderr.write_all(&readbuf[0..size])?;
+            stderr_bytes.extend(&readbuf[0..size]);
 
-        if let Some(status) = child.try_wait()? {
-            break status;
+            if let Some(status) = child.try_wait()? {
+                return Ok(status);
+            }
         }
-    };
+    })?;
+
+    let stderr = std::str::from_utf8(&stderr_bytes)
+        .context("Failed to decode command STDERR output as UTF-8")?;
+
+    ensure_cmd_success(cmd, &status, &stderr_bytes).inspect_err(|_| {
+        if let Some(f) = inspect_err {
+            f(stderr);
+        }
+    })
+}
+
+pub fn run_cmd_with_progress(
+    bin: &Path,
+    args: &[&str],
+    progress: &ProgressBar,
+    inspect_err: Option<ShellErrInspector>,
+) -> Result<()> {
+    run_custom_cmd_with_progress(Command::new(bin).args(args), progress, inspect_err)
+}
+
+/// Run a command, displaying each line it outputs as the message of the provided progress bar
+/// instead of writing it to the terminal
+pub fn run_custom_cmd_with_progress(
+    cmd: &mut Command,
+    progress: &ProgressBar,
+    inspect_err: Option<ShellErrInspector>,
+) -> Result<()> {
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run shell command")?;
+
+    let stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+
+    let stderr_bytes = forward_interrupt_to_child(child.id(), || {
+        std::thread::scope(|s| {
+            let stderr_reader = s.spawn(|| -> Result<Vec<u8>> {
+                let mut stderr_bytes = vec![];
+
+                for line in BufReader::new(stderr).split(b'\n') {
+                    let line = line?;
+                    progress.set_message(String::from_utf8_lossy(&line).trim().to_owned());
+                    stderr_bytes.extend(&line);
+                    stderr_bytes.push(b'\n');
+                }
+
+                Ok(stderr_bytes)
+            });
+
+            for line in BufReader::new(stdout).split(b'\n') {
+                progress.set_message(String::from_utf8_lossy(&line?).trim().to_owned());
+            }
+
+            stderr_reader.join().unwrap()
+        })
+    })?;
+
+    let status = child.wait()?;
 
     let stderr = std::str::from_utf8(&stderr_bytes)
         .context("Failed to decode command STDERR output as UTF-8")?;
@@ -0,0 +1,113 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Get the current time as a UNIX timestamp (in seconds)
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before the UNIX epoch")
+        .as_secs()
+}
+
+/// Format a UNIX timestamp (in seconds) as a UTC date and time
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = timestamp / SECS_PER_DAY;
+    let secs_in_day = timestamp % SECS_PER_DAY;
+
+    let (year, month, day) = civil_from_days(days as i64);
+
+    format!(
+        "{year:0>4}-{month:0>2}-{day:0>2} {:0>2}:{:0>2} UTC",
+        secs_in_day / 3600,
+        (secs_in_day % 3600) / 60
+    )
+}
+
+/// Format a duration in a short human-readable way (e.g. "3d 4h")
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    let (days, hours, minutes) = (
+        secs / SECS_PER_DAY,
+        (secs % SECS_PER_DAY) / 3600,
+        (secs % 3600) / 60,
+    );
+
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Parse a duration made of a number and a unit (e.g. "30m", "12h", "7d")
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let split_at = input
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("Missing unit in duration '{input}' (expected s, m, h, d or w)"))?;
+
+    let (value, unit) = input.split_at(split_at);
+
+    let value = value
+        .parse::<u64>()
+        .map_err(|_| format!("Invalid number in duration '{input}'"))?;
+
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => SECS_PER_DAY,
+        "w" => 7 * SECS_PER_DAY,
+        _ => {
+            return Err(format!(
+                "Unknown duration unit '{unit}' (expected s, m, h, d or w)"
+            ));
+        }
+    };
+
+    let secs = value
+        .checked_mul(unit_secs)
+        .ok_or_else(|| format!("Duration '{input}' is too large"))?;
+
+    Ok(Duration::from_secs(secs))
+}
+
+/// Format a UNIX timestamp (in seconds) as a `YYYYMMDD` date, as used by YT-DLP for upload dates
+pub fn format_upload_date(timestamp: u64) -> String {
+    let (year, month, day) = civil_from_days((timestamp / SECS_PER_DAY) as i64);
+
+    format!("{year:0>4}{month:0>2}{day:0>2}")
+}
+
+/// Parse a date as either `YYYY-MM-DD` or `YYYYMMDD`, returning the latter form
+/// (as used by YT-DLP for upload dates)
+pub fn parse_upload_date(input: &str) -> Result<String, String> {
+    let date = input.replace('-', "");
+
+    if date.len() != 8 || !date.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid date '{input}' (expected YYYY-MM-DD)"));
+    }
+
+    Ok(date)
+}
+
+/// Compute the date in the proleptic Gregorian calendar from a number of days since the UNIX epoch
+pub fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u8;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u8;
+    let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+
+    (year, month, day)
+}
+
+pub static SECS_PER_DAY: u64 = 24 * 3600;
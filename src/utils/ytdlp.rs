@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{fmt, path::Path, process::Command, str::FromStr};
 
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
@@ -11,6 +11,17 @@ use super::shell::run_cmd;
 #[derive(Deserialize)]
 pub struct RawPlaylist {
     pub entries: Vec<RawVideoInfos>,
+
+    #[serde(flatten)]
+    pub infos: RawPlaylistInfos,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RawPlaylistInfos {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub uploader: Option<String>,
+    pub channel: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -18,6 +29,15 @@ pub struct RawVideoInfos {
     pub ie_key: String,
     pub title: String,
     pub url: String,
+
+    /// Duration in seconds
+    pub duration: Option<f64>,
+
+    /// Upload date as `YYYYMMDD`
+    pub upload_date: Option<String>,
+
+    pub uploader: Option<String>,
+    pub channel: Option<String>,
 }
 
 pub fn check_version(bin: &Path) -> Result<String> {
@@ -45,11 +65,263 @@ pub fn fetch_playlist(
     })
 }
 
-pub fn check_availability(bin: &Path, url: &str) -> Result<bool> {
-    // TODO: detect if error is caused by video being unavailable or by another error in YT-DLP
-    Ok(run_cmd(bin, &["--get-url", url]).is_ok())
+/// Download the thumbnail of a playlist (e.g. a channel's avatar) as a JPEG image, without downloading any video
+///
+/// The output path must not include an extension.
+pub fn download_playlist_thumbnail(
+    bin: &Path,
+    url: &str,
+    output: &Path,
+    cookies: Option<&UseCookiesFrom>,
+) -> Result<()> {
+    // Escape the path as it's used as an output template
+    let output = format!(
+        "pl_thumbnail:{}.%(ext)s",
+        output
+            .to_str()
+            .context("Output path contains invalid UTF-8 characters")?
+            .replace('%', "%%")
+    );
+
+    let mut args = vec![
+        "--skip-download",
+        "--write-thumbnail",
+        "--convert-thumbnails",
+        "jpg",
+        "--playlist-items",
+        "0",
+        "-o",
+        &output,
+        url,
+    ];
+
+    if let Some(cookies) = cookies {
+        append_cookies_args(&mut args, cookies)?;
+    }
+
+    run_cmd(bin, &args)?;
+
+    Ok(())
+}
+
+/// Download the subtitles of a video without downloading the video itself
+///
+/// Subtitles are written next to the provided path, with their language and format as extension
+/// (e.g. `<output>.en.vtt`).
+pub fn download_subtitles(
+    bin: &Path,
+    url: &str,
+    output: &Path,
+    subtitles_args: &[String],
+    cookies: Option<&UseCookiesFrom>,
+) -> Result<()> {
+    // Escape the path as it's used as an output template
+    let output = format!(
+        "subtitle:{}.%(ext)s",
+        output
+            .to_str()
+            .context("Output path contains invalid UTF-8 characters")?
+            .replace('%', "%%")
+    );
+
+    let mut args = vec!["--skip-download"];
+
+    args.extend(subtitles_args.iter().map(String::as_str));
+    args.extend(["-o", &output, url]);
+
+    if let Some(cookies) = cookies {
+        append_cookies_args(&mut args, cookies)?;
+    }
+
+    run_cmd(bin, &args)?;
+
+    Ok(())
+}
+
+/// Check if a video is available, returning the reason of the failure if it isn't
+pub fn check_availability(bin: &Path, url: &str) -> Result<Option<YtDlpFailure>> {
+    let output = Command::new(bin)
+        .args(["--get-url", url])
+        .output()
+        .context("Failed to run YT-DLP")?;
+
+    if output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(YtDlpFailure::classify(&String::from_utf8_lossy(
+        &output.stderr,
+    ))))
+}
+
+/// Cause of a YT-DLP failure, determined from its error messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YtDlpFailure {
+    Private,
+    Deleted,
+    GeoBlocked,
+    MembersOnly,
+    AgeGated,
+    RateLimited,
+    Network,
+    ExtractorBroken,
+    Unknown,
+}
+
+impl YtDlpFailure {
+    pub fn classify(stderr: &str) -> Self {
+        let stderr = stderr.to_lowercase();
+
+        // Order matters as some messages contain more generic ones (e.g. "Video unavailable. This video is private")
+        FAILURE_PATTERNS
+            .iter()
+            .find(|(_, patterns)| patterns.iter().any(|pattern| stderr.contains(pattern)))
+            .map_or(Self::Unknown, |(failure, _)| *failure)
+    }
+
+    /// Check if the failure will happen again on every try
+    /// (other failures may be caused by a temporary problem or a missing configuration)
+    pub fn is_permanent(self) -> bool {
+        match self {
+            Self::Private | Self::Deleted | Self::GeoBlocked => true,
+
+            Self::MembersOnly
+            | Self::AgeGated
+            | Self::RateLimited
+            | Self::Network
+            | Self::ExtractorBroken
+            | Self::Unknown => false,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Private => "private",
+            Self::Deleted => "deleted",
+            Self::GeoBlocked => "geo-blocked",
+            Self::MembersOnly => "members-only",
+            Self::AgeGated => "age-gated",
+            Self::RateLimited => "rate-limited",
+            Self::Network => "network",
+            Self::ExtractorBroken => "extractor-broken",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl fmt::Display for YtDlpFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
+impl FromStr for YtDlpFailure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            Self::Private,
+            Self::Deleted,
+            Self::GeoBlocked,
+            Self::MembersOnly,
+            Self::AgeGated,
+            Self::RateLimited,
+            Self::Network,
+            Self::ExtractorBroken,
+            Self::Unknown,
+        ]
+        .into_iter()
+        .find(|failure| failure.name() == s)
+        .ok_or_else(|| format!("Unknown failure category: {s}"))
+    }
+}
+
+static FAILURE_PATTERNS: &[(YtDlpFailure, &[&str])] = &[
+    (
+        YtDlpFailure::Private,
+        &["private video", "video is private"],
+    ),
+    (
+        YtDlpFailure::MembersOnly,
+        &[
+            "members-only",
+            "members only",
+            "join this channel to get access",
+            "available to this channel's members",
+        ],
+    ),
+    (
+        YtDlpFailure::AgeGated,
+        &[
+            "confirm your age",
+            "age-restricted",
+            "age restricted",
+            "inappropriate for some users",
+        ],
+    ),
+    (
+        YtDlpFailure::GeoBlocked,
+        &[
+            "available in your country",
+            "geo restriction",
+            "geo-restricted",
+            "blocked it in your country",
+        ],
+    ),
+    (
+        YtDlpFailure::RateLimited,
+        &[
+            "http error 429",
+            "too many requests",
+            "rate-limited",
+            "rate limit",
+            "not a bot",
+            // YouTube's throttling message: "Video unavailable. This content isn't available, try again later"
+            "content isn't available",
+            "try again later",
+        ],
+    ),
+    (
+        YtDlpFailure::Network,
+        &[
+            "urlopen error",
+            "connection reset",
+            "connection refused",
+            "connection aborted",
+            "timed out",
+            "name or service not known",
+            "temporary failure in name resolution",
+            "network is unreachable",
+            "http error 500",
+            "http error 502",
+            "http error 503",
+            "http error 504",
+        ],
+    ),
+    (
+        YtDlpFailure::Deleted,
+        // Generic messages (e.g. "Video unavailable" alone) are also used for temporary failures
+        &[
+            "this video has been removed",
+            "removed by the uploader",
+            "this video is no longer available",
+            "account associated with this video has been terminated",
+        ],
+    ),
+    (
+        YtDlpFailure::ExtractorBroken,
+        &[
+            "unsupported url",
+            "unable to extract",
+            "please report this issue",
+            "update to the latest version",
+            "failed to parse json",
+            "signature extraction failed",
+            "nsig extraction failed",
+        ],
+    ),
+];
+
 pub fn append_cookies_args<'a>(
     ytdlp_args: &mut Vec<&'a str>,
     cookies: &'a UseCookiesFrom,